@@ -1,23 +1,19 @@
 use bevy::asset::AssetMetaCheck;
 use bevy::prelude::*;
-use bevy::window::WindowMode;
 
-use roguelike_demo::GamePlugin; // ToDo: Replace bevy_game with your new crate name.
+use roguelike_demo::{GamePlugin, Settings}; // ToDo: Replace bevy_game with your new crate name.
 
 #[bevy_main]
 fn main() {
+    // The window is created as the player last set it
+    let settings = Settings::load();
     App::new()
         .insert_resource(AssetMetaCheck::Never)
-        .add_plugins((
-            DefaultPlugins.set(WindowPlugin {
-                primary_window: Some(Window {
-                    resizable: false,
-                    mode: WindowMode::BorderlessFullscreen,
-                    ..default()
-                }),
-                ..default()
-            }),
-            GamePlugin,
-        ))
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(settings.window()),
+            ..default()
+        }))
+        .insert_resource(settings)
+        .add_plugins(GamePlugin)
         .run()
 }
@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+
+use super::manifest::{AssetManifest, MANIFEST_PATH};
+use super::sets::LoadedAssetSets;
+use super::{AssetLoadingState, AssetRegistry, ManifestHandle, RatedTexts};
+use crate::content::ContentFilter;
+
+/// Applies changes made to asset files while the game runs. Changes are
+/// only seen when the asset server watches the assets folder, which the
+/// `dev` feature turns on.
+pub struct HotReloadPlugin;
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                reload_manifest.run_if(in_state(AssetLoadingState::DoneLoading)),
+                log_reloaded::<Image>("texture"),
+                log_reloaded::<Font>("font"),
+                log_reloaded::<AudioSource>("audio"),
+            )
+                .run_if(watching_for_changes),
+        )
+        .add_systems(
+            PostUpdate,
+            rebind_textures.run_if(resource_changed::<AssetRegistry>),
+        );
+    }
+}
+
+/// Keeps the `UiImage` or sprite texture of an entity on the texture
+/// registered under this key, following the key to a new file when the
+/// manifest changes.
+#[derive(Component)]
+pub struct RegistryTexture(pub &'static str);
+
+fn watching_for_changes(asset_server: Res<AssetServer>) -> bool {
+    asset_server.watching_for_changes()
+}
+
+/// Log the path of every changed file loaded as an `A`
+pub fn log_reloaded<A: Asset>(
+    kind: &'static str,
+) -> impl FnMut(EventReader<AssetEvent<A>>, Res<AssetServer>) {
+    move |mut asset_events, asset_server| {
+        for event in asset_events.read() {
+            if let AssetEvent::Modified { id } = event {
+                // Assets without a path, like font atlases, are changed by the game itself
+                if let Some(path) = asset_server.get_path(*id) {
+                    info!("Reloaded {kind} {path}");
+                }
+            }
+        }
+    }
+}
+
+/// Queue the assets of a changed manifest in a fresh registry
+#[allow(clippy::too_many_arguments)]
+fn reload_manifest(
+    mut manifest_events: EventReader<AssetEvent<AssetManifest>>,
+    asset_server: Res<AssetServer>,
+    manifest_handle: Res<ManifestHandle>,
+    manifests: Res<Assets<AssetManifest>>,
+    filter: Res<ContentFilter>,
+    mut registry: ResMut<AssetRegistry>,
+    mut rated_texts: ResMut<RatedTexts>,
+    mut loaded_sets: ResMut<LoadedAssetSets>,
+) {
+    let modified = manifest_events
+        .read()
+        .any(|event| matches!(event, AssetEvent::Modified { id } if *id == manifest_handle.0.id()));
+    let Some(manifest) = manifests.get(&manifest_handle.0).filter(|_| modified) else {
+        return;
+    };
+
+    // Assets still listed keep their handle, so only new files are loaded,
+    // and the old registry drops the keys no longer listed
+    let mut reloaded = AssetRegistry::default();
+    let core = reloaded
+        .queue_groups(&manifest.core, &asset_server, &filter)
+        .len();
+    for set in manifest.sets.iter() {
+        if loaded_sets.0.contains_key(&set.name) {
+            let queued = reloaded.queue_groups(&set.assets, &asset_server, &filter);
+            loaded_sets.0.insert(set.name.clone(), queued);
+        }
+    }
+    *registry = reloaded;
+    rated_texts.0.clone_from(&manifest.text);
+
+    info!(
+        "Reloaded asset manifest {MANIFEST_PATH}: {core} core assets, {} loaded asset sets",
+        loaded_sets.0.len()
+    );
+}
+
+fn rebind_textures(
+    registry: Res<AssetRegistry>,
+    mut ui_image_query: Query<(&RegistryTexture, &mut UiImage)>,
+    mut sprite_query: Query<(&RegistryTexture, &mut Handle<Image>)>,
+) {
+    for (key, mut image) in &mut ui_image_query {
+        let texture = registry.texture(key.0);
+        if image.texture != texture {
+            image.texture = texture;
+        }
+    }
+    for (key, mut handle) in &mut sprite_query {
+        let texture = registry.texture(key.0);
+        if *handle != texture {
+            *handle = texture;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assetloader::manifest::{AssetGroups, ManifestEntry};
+
+    // Registry with the texture `logo` read from `path`
+    fn registry(app: &App, path: &str) -> AssetRegistry {
+        let groups = AssetGroups {
+            textures: vec![ManifestEntry {
+                key: String::from("logo"),
+                path: String::from(path),
+                bytes: None,
+                rating: default(),
+                censored: None,
+            }],
+            ..default()
+        };
+        let mut registry = AssetRegistry::default();
+        registry.queue_groups(
+            &groups,
+            app.world.resource::<AssetServer>(),
+            &ContentFilter::default(),
+        );
+        registry
+    }
+
+    #[test]
+    fn textures_follow_their_key_to_the_new_file() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .add_plugins(HotReloadPlugin);
+        let old = registry(&app, "textures/bevy.png");
+        let old_texture = old.texture("logo");
+        app.insert_resource(old);
+        let sprite = app
+            .world
+            .spawn((RegistryTexture("logo"), old_texture.clone()))
+            .id();
+        let ui_image = app
+            .world
+            .spawn((RegistryTexture("logo"), UiImage::new(old_texture.clone())))
+            .id();
+        app.update();
+        assert_eq!(app.world.get::<Handle<Image>>(sprite), Some(&old_texture));
+
+        // The manifest now lists another file under the key
+        let new = registry(&app, "textures/github.png");
+        let new_texture = new.texture("logo");
+        assert_ne!(new_texture, old_texture);
+        app.insert_resource(new);
+        app.update();
+        assert_eq!(app.world.get::<Handle<Image>>(sprite), Some(&new_texture));
+        assert_eq!(
+            app.world
+                .get::<UiImage>(ui_image)
+                .map(|image| &image.texture),
+            Some(&new_texture)
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use bevy::asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::content::{ContentFilter, ContentRating};
+
+// Path of the manifest, relative to the assets folder
+pub const MANIFEST_PATH: &str = "assets.manifest.ron";
+
+/// Every asset the game loads: the core assets loaded while in
+/// `GameState::AssetLoading`, and the sets loaded only while needed.
+#[derive(Asset, TypePath, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct AssetManifest {
+    pub core: AssetGroups,
+    pub sets: Vec<AssetSetManifest>,
+    // Rated entries of the string tables, every other text is `General`
+    pub text: Vec<RatedText>,
+}
+
+// Assets grouped by the asset type they are loaded as
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct AssetGroups {
+    pub fonts: Vec<ManifestEntry>,
+    pub textures: Vec<ManifestEntry>,
+    pub audio: Vec<ManifestEntry>,
+    pub data: Vec<ManifestEntry>,
+}
+
+/// Assets loaded in the background while the game is in one of the
+/// `preload_in` states, and that must be loaded before entering one of the
+/// `required_in` states. States are given by their `GameState` variant name.
+/// Sets the `ContentFilter` does not allow are never loaded.
+#[derive(Deserialize, Debug)]
+pub struct AssetSetManifest {
+    pub name: String,
+    #[serde(default)]
+    pub rating: ContentRating,
+    #[serde(default)]
+    pub required_in: Vec<String>,
+    #[serde(default)]
+    pub preload_in: Vec<String>,
+    #[serde(default)]
+    pub assets: AssetGroups,
+}
+
+// One asset of the manifest, looked up in the registry by `key`
+#[derive(Deserialize, Debug, Clone)]
+pub struct ManifestEntry {
+    pub key: String,
+    pub path: String,
+    // File size used to weight the loading progress, read from the file
+    // system when not given
+    #[serde(default)]
+    pub bytes: Option<u64>,
+    #[serde(default)]
+    pub rating: ContentRating,
+    // Loaded instead of `path` when the rating is filtered out, the asset
+    // is not loaded at all without one
+    #[serde(default)]
+    pub censored: Option<String>,
+}
+
+impl ManifestEntry {
+    /// Path of the file loaded for the asset under `filter`
+    pub fn path_for(&self, filter: &ContentFilter) -> Option<&str> {
+        if filter.allows(self.rating) {
+            Some(&self.path)
+        } else {
+            self.censored.as_deref()
+        }
+    }
+}
+
+/// A string table entry shown only when the `ContentFilter` allows its
+/// rating, the `censored` entry or `content.censored` is shown instead
+#[derive(Deserialize, Debug, Clone)]
+pub struct RatedText {
+    pub key: String,
+    pub rating: ContentRating,
+    #[serde(default)]
+    pub censored: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum AssetManifestLoaderError {
+    #[error("could not read asset manifest: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse asset manifest: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+#[derive(Default)]
+pub struct AssetManifestLoader;
+
+impl AssetLoader for AssetManifestLoader {
+    type Asset = AssetManifest;
+    type Settings = ();
+    type Error = AssetManifestLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<AssetManifest>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["manifest.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::SAFE_BUILD;
+
+    fn entry(rating: ContentRating, censored: Option<&str>) -> ManifestEntry {
+        ManifestEntry {
+            key: String::from("portrait"),
+            path: String::from("textures/portrait.png"),
+            bytes: None,
+            rating,
+            censored: censored.map(String::from),
+        }
+    }
+
+    #[test]
+    fn safe_mode_picks_the_censored_file() {
+        let safe = ContentFilter { safe_mode: true };
+        let explicit = entry(
+            ContentRating::Explicit,
+            Some("textures/portrait_censored.png"),
+        );
+        assert_eq!(
+            explicit.path_for(&safe),
+            Some("textures/portrait_censored.png")
+        );
+        let general = entry(
+            ContentRating::General,
+            Some("textures/portrait_censored.png"),
+        );
+        assert_eq!(general.path_for(&safe), Some("textures/portrait.png"));
+    }
+
+    #[test]
+    fn explicit_files_without_a_censored_one_are_not_loaded_in_safe_mode() {
+        let explicit = entry(ContentRating::Explicit, None);
+        assert_eq!(explicit.path_for(&ContentFilter { safe_mode: true }), None);
+    }
+
+    #[test]
+    fn without_safe_mode_the_file_itself_is_loaded() {
+        let explicit = entry(
+            ContentRating::Explicit,
+            Some("textures/portrait_censored.png"),
+        );
+        let path = explicit.path_for(&ContentFilter { safe_mode: false });
+        // Unless the build itself is safe
+        if SAFE_BUILD {
+            assert_eq!(path, Some("textures/portrait_censored.png"));
+        } else {
+            assert_eq!(path, Some("textures/portrait.png"));
+        }
+    }
+}
@@ -0,0 +1,688 @@
+use bevy::asset::{
+    LoadState, RecursiveDependencyLoadState, UntypedAssetId, UntypedAssetLoadFailedEvent,
+};
+use bevy::prelude::*;
+use std::time::Duration;
+
+use crate::content::ContentFilter;
+use crate::fonts::FontStackText;
+use crate::gamestate::{AppDespawnOnExitExt, DespawnOnExit, GameState};
+use crate::locale::{tr, Strings};
+use crate::ui::widgets::UiTheme;
+
+pub mod hotreload;
+pub mod manifest;
+pub mod registry;
+pub mod sets;
+
+pub use hotreload::RegistryTexture;
+use manifest::{AssetManifest, AssetManifestLoader, RatedText, MANIFEST_PATH};
+pub use registry::AssetRegistry;
+
+// Game loading states
+#[derive(States, Debug, Hash, Default, Eq, PartialEq, Clone)]
+pub enum AssetLoadingState {
+    #[default]
+    NotLoaded,
+    LoadingManifest,
+    Loading,
+    DoneLoading,
+    FailedLoading,
+}
+
+// Handle of the asset manifest being read
+#[derive(Resource)]
+struct ManifestHandle(Handle<AssetManifest>);
+
+// Assets queued from the manifest, in manifest order, to check loading status
+#[derive(Resource)]
+struct LoadAssetIdVec(Vec<QueuedAsset>);
+
+struct QueuedAsset {
+    id: UntypedAssetId,
+    // String key of the group name shown while loading
+    group: &'static str,
+    path: String,
+    // Weight of the asset in the progress bar
+    bytes: Option<u64>,
+}
+
+/// Assets that could not be loaded, with the reason the load failed
+#[derive(Resource, Default)]
+pub struct FailedAssets(pub Vec<FailedAsset>);
+
+pub struct FailedAsset {
+    pub id: UntypedAssetId,
+    pub path: String,
+    pub reason: String,
+}
+
+impl FailedAssets {
+    // Only the first reason given for an asset is kept
+    fn record(&mut self, id: UntypedAssetId, path: String, reason: String) {
+        if self.0.iter().all(|failed| failed.id != id) {
+            self.0.push(FailedAsset { id, path, reason });
+        }
+    }
+}
+
+/// The rated string table entries of the manifest
+#[derive(Resource, Default)]
+pub struct RatedTexts(pub Vec<RatedText>);
+
+/// Sent to load the assets in `FailedAssets` again
+#[derive(Event)]
+pub struct RetryAssetLoading;
+
+// Store the loading status data
+#[derive(Resource, Default)]
+struct LoadStatus {
+    total: u64,
+    loaded: u64,
+    total_bytes: u64,
+    loaded_bytes: u64,
+    // Loaded and total asset count of each manifest group
+    groups: Vec<(&'static str, u64, u64)>,
+    // Path of the first queued asset still loading
+    in_flight: Option<String>,
+    // Real time elapsed when the assets were queued
+    started: Duration,
+    elapsed: Duration,
+}
+
+impl LoadStatus {
+    fn progress(&self) -> f32 {
+        if self.total_bytes == 0 {
+            return 1.0;
+        }
+        self.loaded_bytes as f32 / self.total_bytes as f32
+    }
+
+    // Assume the remaining bytes load as fast as the loaded ones did
+    fn remaining(&self) -> Option<Duration> {
+        if self.loaded_bytes == 0 {
+            return None;
+        }
+        let spent = self.elapsed.saturating_sub(self.started);
+        let remaining_bytes = self.total_bytes - self.loaded_bytes;
+        Some(spent.mul_f64(remaining_bytes as f64 / self.loaded_bytes as f64))
+    }
+}
+
+#[derive(Component)]
+struct ProgressBar;
+
+#[derive(Component)]
+struct ProgressText;
+
+pub struct AssetLoaderPlugin;
+
+impl Plugin for AssetLoaderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<AssetLoadingState>()
+            .add_despawn_on_exit::<AssetLoadingState>()
+            .init_asset::<AssetManifest>()
+            .init_asset_loader::<AssetManifestLoader>()
+            .init_resource::<AssetRegistry>()
+            .init_resource::<ContentFilter>()
+            .init_resource::<RatedTexts>()
+            .init_resource::<FailedAssets>()
+            .add_event::<RetryAssetLoading>()
+            .insert_resource(LoadAssetIdVec(Vec::new()))
+            .init_resource::<LoadStatus>()
+            .add_systems(OnEnter(GameState::AssetLoading), load_manifest)
+            .add_systems(
+                Update,
+                (record_load_failures, load_assets)
+                    .chain()
+                    .run_if(in_state(AssetLoadingState::LoadingManifest)),
+            )
+            .add_systems(
+                OnEnter(AssetLoadingState::Loading),
+                (start_load_timer, spawn_loading_menu),
+            )
+            .add_systems(
+                Update,
+                (
+                    record_load_failures,
+                    poll_all_load_status,
+                    update_load_bar,
+                    update_load_text,
+                )
+                    .chain()
+                    .run_if(in_state(AssetLoadingState::Loading)),
+            )
+            .add_systems(
+                Update,
+                retry_failed_assets.run_if(in_state(AssetLoadingState::FailedLoading)),
+            )
+            .add_systems(
+                Update,
+                refilter_assets.run_if(in_state(AssetLoadingState::DoneLoading)),
+            );
+    }
+}
+
+/// `load_manifest` Only run once entering the Loading state
+fn load_manifest(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut next_load_state: ResMut<NextState<AssetLoadingState>>,
+) {
+    commands.insert_resource(ManifestHandle(asset_server.load(MANIFEST_PATH)));
+    next_load_state.set(AssetLoadingState::LoadingManifest);
+}
+
+/// `load_assets` Queue every asset of the manifest once it is read
+#[allow(clippy::too_many_arguments)]
+fn load_assets(
+    asset_server: Res<AssetServer>,
+    manifest_handle: Res<ManifestHandle>,
+    manifests: Res<Assets<AssetManifest>>,
+    filter: Res<ContentFilter>,
+    mut registry: ResMut<AssetRegistry>,
+    mut rated_texts: ResMut<RatedTexts>,
+    mut asset_ids: ResMut<LoadAssetIdVec>,
+    mut load_status: ResMut<LoadStatus>,
+    mut failed_assets: ResMut<FailedAssets>,
+    mut next_load_state: ResMut<NextState<AssetLoadingState>>,
+) {
+    let Some(manifest) = manifests.get(&manifest_handle.0) else {
+        if asset_server.load_state(&manifest_handle.0) == LoadState::Failed {
+            failed_assets.record(
+                manifest_handle.0.id().untyped(),
+                String::from(MANIFEST_PATH),
+                String::from("the asset manifest could not be loaded"),
+            );
+            next_load_state.set(AssetLoadingState::FailedLoading);
+        }
+        return;
+    };
+
+    asset_ids.0 = registry.queue_groups(&manifest.core, &asset_server, &filter);
+    rated_texts.0.clone_from(&manifest.text);
+    weigh_assets(&mut asset_ids.0);
+    // Set the total number and size of assets
+    load_status.total = asset_ids.0.len() as u64;
+    load_status.total_bytes = asset_ids.0.iter().filter_map(|asset| asset.bytes).sum();
+
+    // Move state to Loading
+    next_load_state.set(AssetLoadingState::Loading);
+}
+
+/// Keep the reason of every failed load of the manifest or a queued asset
+fn record_load_failures(
+    mut failed_events: EventReader<UntypedAssetLoadFailedEvent>,
+    manifest_handle: Res<ManifestHandle>,
+    asset_vec: Res<LoadAssetIdVec>,
+    mut failed_assets: ResMut<FailedAssets>,
+) {
+    for event in failed_events.read() {
+        let queued = asset_vec.0.iter().any(|asset| asset.id == event.id);
+        if event.id == manifest_handle.0.id().untyped() || queued {
+            failed_assets.record(event.id, event.path.to_string(), event.error.to_string());
+        }
+    }
+}
+
+/// System that polls all load status and moves states
+fn poll_all_load_status(
+    mut next_load_state: ResMut<NextState<AssetLoadingState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    asset_server: Res<AssetServer>,
+    asset_vec: Res<LoadAssetIdVec>,
+    time: Res<Time<Real>>,
+    mut load_status: ResMut<LoadStatus>,
+    mut failed_assets: ResMut<FailedAssets>,
+) {
+    // Clear loaded count
+    load_status.loaded = 0;
+    load_status.loaded_bytes = 0;
+    load_status.groups.clear();
+    load_status.in_flight = None;
+    load_status.elapsed = time.elapsed();
+    for asset in asset_vec.0.iter() {
+        let state = asset_server.get_recursive_dependency_load_state(asset.id);
+        let loaded = state == Some(RecursiveDependencyLoadState::Loaded);
+
+        match load_status.groups.last_mut() {
+            Some((group, group_loaded, group_total)) if *group == asset.group => {
+                *group_loaded += loaded as u64;
+                *group_total += 1;
+            }
+            _ => load_status.groups.push((asset.group, loaded as u64, 1)),
+        }
+
+        let reason = match state {
+            Some(RecursiveDependencyLoadState::Loaded) => {
+                load_status.loaded += 1;
+                load_status.loaded_bytes += asset.bytes.unwrap_or_default();
+                continue;
+            }
+            // Failures of the asset itself are recorded from the failed events
+            Some(RecursiveDependencyLoadState::Failed) => "a dependency failed to load",
+            None => "the asset server is not tracking this asset",
+            _ => {
+                if load_status.in_flight.is_none() {
+                    load_status.in_flight = Some(asset.path.clone());
+                }
+                continue;
+            }
+        };
+        failed_assets.record(asset.id, asset.path.clone(), String::from(reason));
+    }
+
+    if load_status.loaded == load_status.total {
+        next_load_state.set(AssetLoadingState::DoneLoading);
+        next_game_state.set(GameState::DisclaimerMenu);
+    } else if load_status.loaded + failed_assets.0.len() as u64 == load_status.total {
+        // Only give up once every other asset is settled, so all failures are listed
+        next_load_state.set(AssetLoadingState::FailedLoading);
+    }
+}
+
+/// Load the failed assets again when asked by the failure screen
+#[allow(clippy::too_many_arguments)]
+fn retry_failed_assets(
+    mut retry_events: EventReader<RetryAssetLoading>,
+    asset_server: Res<AssetServer>,
+    mut manifest_handle: ResMut<ManifestHandle>,
+    manifests: Res<Assets<AssetManifest>>,
+    filter: Res<ContentFilter>,
+    mut registry: ResMut<AssetRegistry>,
+    mut failed_assets: ResMut<FailedAssets>,
+    mut next_load_state: ResMut<NextState<AssetLoadingState>>,
+) {
+    if retry_events.read().count() == 0 {
+        return;
+    }
+
+    let failed_ids: Vec<UntypedAssetId> = failed_assets.0.drain(..).map(|f| f.id).collect();
+    match manifests.get(&manifest_handle.0) {
+        Some(manifest) => {
+            registry.requeue_failed(&manifest.core, &asset_server, &filter, &failed_ids);
+            next_load_state.set(AssetLoadingState::Loading);
+        }
+        None => {
+            // Nothing was queued without a manifest, start over from it
+            manifest_handle.0 = asset_server.load(MANIFEST_PATH);
+            next_load_state.set(AssetLoadingState::LoadingManifest);
+        }
+    }
+}
+
+/// Swap the rated core assets for the files the changed filter picks
+fn refilter_assets(
+    filter: Res<ContentFilter>,
+    asset_server: Res<AssetServer>,
+    manifest_handle: Res<ManifestHandle>,
+    manifests: Res<Assets<AssetManifest>>,
+    mut registry: ResMut<AssetRegistry>,
+) {
+    if !filter.is_changed() || filter.is_added() {
+        return;
+    }
+    let Some(manifest) = manifests.get(&manifest_handle.0) else {
+        return;
+    };
+    registry.refilter(&manifest.core, &asset_server, &filter);
+}
+
+/// Give every queued asset its size in bytes as progress weight
+fn weigh_assets(assets: &mut [QueuedAsset]) {
+    for asset in assets.iter_mut() {
+        asset.bytes = asset.bytes.or_else(|| file_size(&asset.path));
+    }
+    // Assets of unknown size weigh as much as an average known one
+    let known: Vec<u64> = assets.iter().filter_map(|asset| asset.bytes).collect();
+    let average = match known.len() {
+        0 => 1,
+        len => (known.iter().sum::<u64>() / len as u64).max(1),
+    };
+    for asset in assets.iter_mut() {
+        asset.bytes.get_or_insert(average);
+    }
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+fn file_size(path: &str) -> Option<u64> {
+    use bevy::asset::io::file::FileAssetReader;
+
+    let reader = FileAssetReader::new(AssetPlugin::default().file_path);
+    std::fs::metadata(reader.root_path().join(path))
+        .ok()
+        .map(|metadata| metadata.len())
+}
+
+// Assets are not plain files on the web and on android
+#[cfg(any(target_arch = "wasm32", target_os = "android"))]
+fn file_size(_path: &str) -> Option<u64> {
+    None
+}
+
+fn start_load_timer(time: Res<Time<Real>>, mut load_status: ResMut<LoadStatus>) {
+    load_status.started = time.elapsed();
+}
+
+fn update_load_bar(
+    mut bar_query: Query<&mut Style, With<ProgressBar>>,
+    load_status: Res<LoadStatus>,
+) {
+    let percent = load_status.progress();
+    bar_query.get_single_mut().unwrap().width = Val::Percent(100.0 * percent);
+}
+
+fn update_load_text(
+    mut text_query: Query<&mut FontStackText, With<ProgressText>>,
+    load_status: Res<LoadStatus>,
+    strings: Res<Strings>,
+) {
+    // The first load shows this screen before the string tables are loaded,
+    // only the bar moves until they are
+    if strings.is_empty() {
+        return;
+    }
+    let mut text = text_query.get_single_mut().unwrap();
+    // Fonts 2/2, Audio 1/4
+    text.sections[0].value = load_status
+        .groups
+        .iter()
+        .map(|(group, loaded, total)| {
+            strings.format(&tr(group).arg("loaded", loaded).arg("total", total))
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    text.sections[1].value = match &load_status.in_flight {
+        Some(path) => format!(
+            "\n{}",
+            strings.format(&tr("loading.in_flight").arg("path", path))
+        ),
+        None => String::new(),
+    };
+    text.sections[2].value = match load_status.remaining() {
+        Some(remaining) => {
+            let seconds = remaining.as_secs() + 1;
+            format!(
+                "\n{}",
+                strings.format(&tr("loading.time_left").arg("seconds", seconds))
+            )
+        }
+        None => String::new(),
+    };
+}
+
+fn spawn_loading_menu(mut commands: Commands, registry: Res<AssetRegistry>, theme: Res<UiTheme>) {
+    // Spawn Bevy Icon Image and Text
+    let spawn_bevy_icon_image = |parent: &mut ChildBuilder| {
+        parent.spawn((
+            RegistryTexture("bevy_logo"),
+            ImageBundle {
+                style: Style {
+                    width: Val::Px(50.0),
+                    height: Val::Px(50.0),
+                    right: Val::Px(10.0),
+                    ..default()
+                },
+                image: UiImage {
+                    texture: registry.texture("bevy_logo"),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    };
+
+    let spawn_bevy_icon_text = |parent: &mut ChildBuilder| {
+        parent.spawn(TextBundle {
+            style: Style {
+                justify_content: JustifyContent::FlexStart,
+                align_items: AlignItems::FlexStart,
+                ..default()
+            },
+            text: Text {
+                sections: vec![TextSection {
+                    value: String::from("Powered by Bevy Engine."),
+                    style: TextStyle {
+                        font_size: 20.0,
+                        color: Color::GRAY,
+                        ..default()
+                    },
+                }],
+                justify: JustifyText::Left,
+                ..default()
+            },
+            ..default()
+        });
+    };
+
+    // Spawn Bevy Icon Node
+    let spawn_bevy_icon_node = |parent: &mut ChildBuilder| {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    height: Val::Px(100.0),
+                    ..default()
+                },
+                visibility: Visibility::Visible,
+                background_color: Color::ANTIQUE_WHITE.into(),
+                ..default()
+            })
+            .with_children(spawn_bevy_icon_image)
+            .with_children(spawn_bevy_icon_text);
+    };
+
+    // Spawn progress bar
+    let spawn_progress_bar = |parent: &mut ChildBuilder| {
+        parent.spawn((
+            ProgressBar,
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(0.0),
+                    height: Val::Px(50.0),
+                    ..default()
+                },
+                visibility: Visibility::Visible,
+                background_color: Color::DARK_GREEN.into(),
+                ..default()
+            },
+        ));
+    };
+
+    // Spawn progress bar node
+    let spawn_progress_bar_node = |parent: &mut ChildBuilder| {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    justify_content: JustifyContent::FlexStart,
+                    align_items: AlignItems::Center,
+                    width: Val::Percent(80.0),
+                    height: Val::Px(50.0),
+                    ..default()
+                },
+                visibility: Visibility::Visible,
+                background_color: Color::WHITE.into(),
+                ..default()
+            })
+            .with_children(spawn_progress_bar);
+    };
+
+    // Spawn per group progress, current asset and time left
+    let spawn_progress_text = |parent: &mut ChildBuilder| {
+        let style = TextStyle {
+            font_size: 20.0,
+            color: Color::GRAY,
+            ..default()
+        };
+        parent.spawn((
+            ProgressText,
+            // Drawn with the theme fonts, which may show Chinese text
+            FontStackText {
+                stack: theme.text_fonts,
+                sections: vec![
+                    TextSection::new("", style.clone()),
+                    TextSection::new("", style.clone()),
+                    TextSection::new("", style),
+                ],
+            },
+            TextBundle {
+                text: Text {
+                    justify: JustifyText::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    };
+
+    commands
+        .spawn((
+            DespawnOnExit(AssetLoadingState::Loading),
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(50.0),
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                visibility: Visibility::Visible,
+                background_color: Color::ANTIQUE_WHITE.into(),
+                ..default()
+            },
+        ))
+        .with_children(spawn_bevy_icon_node)
+        .with_children(spawn_progress_bar_node)
+        .with_children(spawn_progress_text);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+    use crate::gamestate::GameStatePlugin;
+
+    // Write the given files in a fresh asset folder for one test
+    fn asset_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("roguelike_demo_{name}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        for (path, contents) in files {
+            write_asset(&dir, path, contents);
+        }
+        dir
+    }
+
+    fn write_asset(dir: &Path, path: &str, contents: &str) {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    // Headless app running only the loader, without any window or renderer
+    fn loader_app(dir: &Path) -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                file_path: dir.to_string_lossy().into_owned(),
+                ..default()
+            },
+        ))
+        .init_asset::<Font>()
+        .init_asset::<Image>()
+        .init_asset::<AudioSource>()
+        .init_resource::<UiTheme>()
+        .init_resource::<Strings>()
+        .add_plugins((GameStatePlugin, AssetLoaderPlugin));
+        app
+    }
+
+    fn update_until(app: &mut App, state: AssetLoadingState) {
+        for _ in 0..500 {
+            app.update();
+            if *app.world.resource::<State<AssetLoadingState>>().get() == state {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("asset loader never reached {state:?}");
+    }
+
+    const MANIFEST: &str = r#"(
+        core: (
+            data: [
+                (key: "present", path: "data/present.manifest.ron"),
+                (key: "missing", path: "data/missing.manifest.ron"),
+            ],
+        ),
+    )"#;
+
+    #[test]
+    fn missing_file_fails_loading_with_its_path() {
+        let dir = asset_dir(
+            "missing_file",
+            &[
+                (MANIFEST_PATH, MANIFEST),
+                ("data/present.manifest.ron", "()"),
+            ],
+        );
+        let mut app = loader_app(&dir);
+
+        update_until(&mut app, AssetLoadingState::FailedLoading);
+
+        let failed = &app.world.resource::<FailedAssets>().0;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].path, "data/missing.manifest.ron");
+        assert!(!failed[0].reason.is_empty());
+        assert_eq!(
+            *app.world.resource::<State<GameState>>().get(),
+            GameState::AssetLoading
+        );
+    }
+
+    #[test]
+    fn retry_loads_the_failed_assets() {
+        let dir = asset_dir(
+            "retry",
+            &[
+                (MANIFEST_PATH, MANIFEST),
+                ("data/present.manifest.ron", "()"),
+            ],
+        );
+        let mut app = loader_app(&dir);
+        update_until(&mut app, AssetLoadingState::FailedLoading);
+
+        write_asset(&dir, "data/missing.manifest.ron", "()");
+        app.world.send_event(RetryAssetLoading);
+        update_until(&mut app, AssetLoadingState::DoneLoading);
+
+        assert!(app.world.resource::<FailedAssets>().0.is_empty());
+        app.update();
+        assert_eq!(
+            *app.world.resource::<State<GameState>>().get(),
+            GameState::DisclaimerMenu
+        );
+    }
+
+    #[test]
+    fn missing_manifest_fails_loading() {
+        let dir = asset_dir("missing_manifest", &[]);
+        let mut app = loader_app(&dir);
+
+        update_until(&mut app, AssetLoadingState::FailedLoading);
+
+        let failed = &app.world.resource::<FailedAssets>().0;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].path, MANIFEST_PATH);
+    }
+}
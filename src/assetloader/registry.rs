@@ -0,0 +1,259 @@
+use bevy::asset::{LoadedUntypedAsset, UntypedAssetId};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use super::manifest::{AssetGroups, ManifestEntry};
+use super::QueuedAsset;
+use crate::content::{ContentFilter, ContentRating};
+
+/// Typed handles of every asset listed in the asset manifest, looked up by
+/// the key given to the asset in the manifest.
+#[derive(Resource, Default)]
+pub struct AssetRegistry {
+    fonts: HashMap<String, Handle<Font>>,
+    textures: HashMap<String, Handle<Image>>,
+    audio: HashMap<String, Handle<AudioSource>>,
+    data: HashMap<String, Handle<LoadedUntypedAsset>>,
+}
+
+impl AssetRegistry {
+    /// Start loading every asset of the groups the filter allows, or its
+    /// censored variant, returning the assets to poll.
+    pub(super) fn queue_groups(
+        &mut self,
+        groups: &AssetGroups,
+        asset_server: &AssetServer,
+        filter: &ContentFilter,
+    ) -> Vec<QueuedAsset> {
+        let mut queued = Vec::new();
+        let mut queue = QueueContext::new(filter, &mut queued);
+        self.queue(groups, asset_server, &mut queue);
+        queued
+    }
+
+    /// Request again only the assets of the groups whose load failed.
+    pub(super) fn requeue_failed(
+        &mut self,
+        groups: &AssetGroups,
+        asset_server: &AssetServer,
+        filter: &ContentFilter,
+        failed: &[UntypedAssetId],
+    ) {
+        // The ids of requeued assets do not change, so they are not collected
+        let mut queued = Vec::new();
+        let mut queue = QueueContext::new(filter, &mut queued);
+        queue.failed = Some(failed);
+        self.queue(groups, asset_server, &mut queue);
+    }
+
+    /// Load the rated assets of the groups again from the file the changed
+    /// filter picks for them
+    pub(super) fn refilter(
+        &mut self,
+        groups: &AssetGroups,
+        asset_server: &AssetServer,
+        filter: &ContentFilter,
+    ) {
+        let mut queued = Vec::new();
+        let mut queue = QueueContext::new(filter, &mut queued);
+        queue.rated_only = true;
+        self.queue(groups, asset_server, &mut queue);
+    }
+
+    /// Drop the handles of every asset of the groups, letting the asset
+    /// server unload the ones no entity still uses.
+    pub(super) fn remove_groups(&mut self, groups: &AssetGroups) {
+        remove_group(&groups.fonts, &mut self.fonts);
+        remove_group(&groups.textures, &mut self.textures);
+        remove_group(&groups.audio, &mut self.audio);
+        remove_group(&groups.data, &mut self.data);
+    }
+
+    fn queue(
+        &mut self,
+        groups: &AssetGroups,
+        asset_server: &AssetServer,
+        queue: &mut QueueContext,
+    ) {
+        queue.group("loading.fonts", &groups.fonts, &mut self.fonts, |path| {
+            asset_server.load(path.to_owned())
+        });
+        queue.group(
+            "loading.textures",
+            &groups.textures,
+            &mut self.textures,
+            |path| asset_server.load(path.to_owned()),
+        );
+        queue.group("loading.audio", &groups.audio, &mut self.audio, |path| {
+            asset_server.load(path.to_owned())
+        });
+        // Data files are loaded with whatever loader matches their extension
+        queue.group("loading.data", &groups.data, &mut self.data, |path| {
+            asset_server.load_untyped(path.to_owned())
+        });
+    }
+
+    pub fn font(&self, key: &str) -> Handle<Font> {
+        lookup(&self.fonts, key, "font")
+    }
+
+    pub fn texture(&self, key: &str) -> Handle<Image> {
+        lookup(&self.textures, key, "texture")
+    }
+
+    // Nothing plays sounds yet
+    #[allow(dead_code)]
+    pub fn audio(&self, key: &str) -> Handle<AudioSource> {
+        lookup(&self.audio, key, "audio")
+    }
+
+    /// Typed handle of a data file, `None` if the key is unknown, the file is
+    /// not loaded yet or it was loaded as another asset type.
+    pub fn data<A: Asset>(
+        &self,
+        key: &str,
+        loaded: &Assets<LoadedUntypedAsset>,
+    ) -> Option<Handle<A>> {
+        let untyped = loaded.get(self.data.get(key)?)?;
+        untyped.handle.clone().try_typed::<A>().ok()
+    }
+}
+
+fn remove_group<A: Asset>(entries: &[ManifestEntry], handles: &mut HashMap<String, Handle<A>>) {
+    for entry in entries.iter() {
+        handles.remove(&entry.key);
+    }
+}
+
+// What is shared by every group queued at once
+struct QueueContext<'a> {
+    filter: &'a ContentFilter,
+    // Only the assets that failed to load
+    failed: Option<&'a [UntypedAssetId]>,
+    // Only the assets with a rating
+    rated_only: bool,
+    queued: &'a mut Vec<QueuedAsset>,
+}
+
+impl<'a> QueueContext<'a> {
+    fn new(filter: &'a ContentFilter, queued: &'a mut Vec<QueuedAsset>) -> Self {
+        Self {
+            filter,
+            failed: None,
+            rated_only: false,
+            queued,
+        }
+    }
+
+    fn group<A: Asset>(
+        &mut self,
+        group: &'static str,
+        entries: &[ManifestEntry],
+        handles: &mut HashMap<String, Handle<A>>,
+        load: impl Fn(&str) -> Handle<A>,
+    ) {
+        for entry in entries.iter() {
+            if let Some(failed) = self.failed {
+                let has_failed = handles
+                    .get(&entry.key)
+                    .is_some_and(|handle| failed.contains(&handle.id().untyped()));
+                if !has_failed {
+                    continue;
+                }
+            }
+            if self.rated_only && entry.rating == ContentRating::General {
+                continue;
+            }
+            // Filtered files are never loaded, not even in the background
+            let Some(path) = entry.path_for(self.filter) else {
+                handles.remove(&entry.key);
+                continue;
+            };
+            let handle = load(path);
+            self.queued.push(QueuedAsset {
+                id: handle.id().untyped(),
+                group,
+                path: String::from(path),
+                bytes: entry.bytes,
+            });
+            handles.insert(entry.key.clone(), handle);
+        }
+    }
+}
+
+// Unknown keys fall back to the default handle, so a typo shows up as the
+// default font or a white texture instead of a crash
+fn lookup<A: Asset>(handles: &HashMap<String, Handle<A>>, key: &str, kind: &str) -> Handle<A> {
+    handles.get(key).cloned().unwrap_or_else(|| {
+        warn!("No {kind} registered in the asset manifest with key `{key}`");
+        Handle::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str, rating: ContentRating, censored: Option<&str>) -> ManifestEntry {
+        ManifestEntry {
+            key: String::from(key),
+            path: format!("textures/{key}.png"),
+            bytes: None,
+            rating,
+            censored: censored.map(String::from),
+        }
+    }
+
+    // Queue the entries under `filter`, returning the paths loaded and the
+    // keys left registered
+    fn queue(entries: &[ManifestEntry], filter: &ContentFilter) -> (Vec<String>, Vec<String>) {
+        let mut handles: HashMap<String, Handle<Image>> =
+            HashMap::from([(String::from("nude"), Handle::default())]);
+        let mut queued = Vec::new();
+        QueueContext::new(filter, &mut queued).group(
+            "loading.textures",
+            entries,
+            &mut handles,
+            |_| Handle::default(),
+        );
+        let loaded = queued.into_iter().map(|asset| asset.path).collect();
+        let mut keys: Vec<String> = handles.into_keys().collect();
+        keys.sort();
+        (loaded, keys)
+    }
+
+    #[test]
+    fn filtered_assets_load_their_censored_file_or_nothing() {
+        let entries = [
+            entry("wall", ContentRating::General, None),
+            entry(
+                "portrait",
+                ContentRating::Explicit,
+                Some("textures/robed.png"),
+            ),
+            entry("nude", ContentRating::Explicit, None),
+        ];
+        let (loaded, keys) = queue(&entries, &ContentFilter { safe_mode: true });
+        assert_eq!(loaded, ["textures/wall.png", "textures/robed.png"]);
+        // An asset registered before safe mode was turned on is dropped
+        assert_eq!(keys, ["portrait", "wall"]);
+    }
+
+    #[test]
+    fn unfiltered_assets_load_their_own_file() {
+        if crate::content::SAFE_BUILD {
+            return;
+        }
+        let entries = [
+            entry(
+                "portrait",
+                ContentRating::Explicit,
+                Some("textures/robed.png"),
+            ),
+            entry("nude", ContentRating::Explicit, None),
+        ];
+        let (loaded, keys) = queue(&entries, &ContentFilter { safe_mode: false });
+        assert_eq!(loaded, ["textures/portrait.png", "textures/nude.png"]);
+        assert_eq!(keys, ["nude", "portrait"]);
+    }
+}
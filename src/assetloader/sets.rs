@@ -0,0 +1,284 @@
+use bevy::asset::RecursiveDependencyLoadState;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use super::manifest::{AssetManifest, AssetSetManifest};
+use super::{AssetLoadingState, AssetRegistry, ManifestHandle, QueuedAsset};
+use crate::content::ContentFilter;
+use crate::fonts::FontStackText;
+use crate::gamestate::GameState;
+use crate::locale::tr;
+use crate::transition::ScreenTransitionSet;
+use crate::ui::widgets::UiTheme;
+
+/// Loads the asset sets of the manifest while the game state needs them,
+/// and holds back a `GameState` transition until the sets it requires are
+/// loaded.
+pub struct AssetSetPlugin;
+
+impl Plugin for AssetSetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadedAssetSets>()
+            .init_resource::<PendingTransition>()
+            // Before `StateTransition` applies the next state, once the
+            // screen transition fired it
+            .add_systems(
+                PreUpdate,
+                gate_state_transitions
+                    .after(ScreenTransitionSet)
+                    .run_if(in_state(AssetLoadingState::DoneLoading)),
+            )
+            .add_systems(
+                Update,
+                (update_loaded_sets, refilter_loaded_sets)
+                    .run_if(in_state(AssetLoadingState::DoneLoading)),
+            );
+    }
+}
+
+// Queued asset sets by name
+#[derive(Resource, Default)]
+pub(super) struct LoadedAssetSets(pub(super) HashMap<String, Vec<QueuedAsset>>);
+
+// Game state waiting for its asset sets before being entered
+#[derive(Resource, Default)]
+struct PendingTransition(Option<GameState>);
+
+#[derive(Component)]
+struct AssetSetLoadingOverlay;
+
+// Sets name states the way `GameState` variants are written
+fn state_name(state: &GameState) -> String {
+    format!("{state:?}")
+}
+
+fn is_wanted(
+    set: &AssetSetManifest,
+    filter: &ContentFilter,
+    current: &str,
+    pending: Option<&str>,
+) -> bool {
+    let needed = set.required_in.iter().any(|state| state == current)
+        || set.preload_in.iter().any(|state| state == current)
+        || pending.is_some_and(|pending| set.required_in.iter().any(|state| state == pending));
+    needed && filter.allows(set.rating)
+}
+
+/// Queue the sets wanted by the current or pending state, and drop the others
+#[allow(clippy::too_many_arguments)]
+fn update_loaded_sets(
+    game_state: Res<State<GameState>>,
+    pending: Res<PendingTransition>,
+    asset_server: Res<AssetServer>,
+    manifest_handle: Res<ManifestHandle>,
+    manifests: Res<Assets<AssetManifest>>,
+    filter: Res<ContentFilter>,
+    mut registry: ResMut<AssetRegistry>,
+    mut loaded_sets: ResMut<LoadedAssetSets>,
+) {
+    let Some(manifest) = manifests.get(&manifest_handle.0) else {
+        return;
+    };
+    let current = state_name(game_state.get());
+    let pending = pending.0.as_ref().map(state_name);
+
+    for set in manifest.sets.iter() {
+        let wanted = is_wanted(set, &filter, &current, pending.as_deref());
+        let loaded = loaded_sets.0.contains_key(&set.name);
+        if wanted && !loaded {
+            info!("Loading asset set `{}`", set.name);
+            let queued = registry.queue_groups(&set.assets, &asset_server, &filter);
+            loaded_sets.0.insert(set.name.clone(), queued);
+        } else if !wanted && loaded {
+            info!("Unloading asset set `{}`", set.name);
+            registry.remove_groups(&set.assets);
+            loaded_sets.0.remove(&set.name);
+        }
+    }
+}
+
+/// Swap the rated assets of the loaded sets for the files the changed filter
+/// picks, sets no longer allowed are dropped by `update_loaded_sets`
+fn refilter_loaded_sets(
+    filter: Res<ContentFilter>,
+    asset_server: Res<AssetServer>,
+    manifest_handle: Res<ManifestHandle>,
+    manifests: Res<Assets<AssetManifest>>,
+    loaded_sets: Res<LoadedAssetSets>,
+    mut registry: ResMut<AssetRegistry>,
+) {
+    if !filter.is_changed() || filter.is_added() {
+        return;
+    }
+    let Some(manifest) = manifests.get(&manifest_handle.0) else {
+        return;
+    };
+    for set in manifest.sets.iter() {
+        if loaded_sets.0.contains_key(&set.name) {
+            registry.refilter(&set.assets, &asset_server, &filter);
+        }
+    }
+}
+
+// Failed assets count as settled so a broken file cannot block the game,
+// the asset server already logs why they failed. Filtered sets are skipped.
+fn is_ready(
+    manifest: &AssetManifest,
+    filter: &ContentFilter,
+    state: &GameState,
+    loaded_sets: &LoadedAssetSets,
+    asset_server: &AssetServer,
+) -> bool {
+    let name = state_name(state);
+    manifest
+        .sets
+        .iter()
+        .filter(|set| set.required_in.contains(&name) && filter.allows(set.rating))
+        .all(|set| {
+            loaded_sets.0.get(&set.name).is_some_and(|queued| {
+                queued.iter().all(|asset| {
+                    matches!(
+                        asset_server.get_recursive_dependency_load_state(asset.id),
+                        Some(RecursiveDependencyLoadState::Loaded)
+                            | Some(RecursiveDependencyLoadState::Failed)
+                    )
+                })
+            })
+        })
+}
+
+/// Hold back the next `GameState` while the sets it requires are loading
+#[allow(clippy::too_many_arguments)]
+fn gate_state_transitions(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut pending: ResMut<PendingTransition>,
+    asset_server: Res<AssetServer>,
+    manifest_handle: Res<ManifestHandle>,
+    manifests: Res<Assets<AssetManifest>>,
+    filter: Res<ContentFilter>,
+    loaded_sets: Res<LoadedAssetSets>,
+    overlay_query: Query<Entity, With<AssetSetLoadingOverlay>>,
+    theme: Res<UiTheme>,
+) {
+    let Some(manifest) = manifests.get(&manifest_handle.0) else {
+        return;
+    };
+    // A newer transition replaces the one waiting
+    if let Some(next) = next_state.0.take() {
+        pending.0 = Some(next);
+    }
+    let Some(target) = pending.0.clone() else {
+        return;
+    };
+
+    if is_ready(manifest, &filter, &target, &loaded_sets, &asset_server) {
+        next_state.set(target);
+        pending.0 = None;
+        for entity in overlay_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    } else if overlay_query.is_empty() {
+        spawn_loading_overlay(&mut commands, &theme);
+    }
+}
+
+fn spawn_loading_overlay(commands: &mut Commands, theme: &UiTheme) {
+    commands
+        .spawn((
+            AssetSetLoadingOverlay,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(20.0),
+                    bottom: Val::Px(20.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                visibility: Visibility::Visible,
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                tr("loading.title"),
+                FontStackText {
+                    stack: theme.text_fonts,
+                    sections: vec![TextSection::new(
+                        "",
+                        TextStyle {
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    )],
+                },
+                TextBundle {
+                    text: Text {
+                        justify: JustifyText::Right,
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assetloader::manifest::AssetGroups;
+    use crate::content::{ContentRating, SAFE_BUILD};
+
+    fn set(rating: ContentRating) -> AssetSetManifest {
+        AssetSetManifest {
+            name: String::from("dungeon"),
+            rating,
+            required_in: vec![String::from("GameRunning")],
+            preload_in: vec![String::from("MainMenu")],
+            assets: AssetGroups::default(),
+        }
+    }
+
+    #[test]
+    fn sets_are_wanted_by_their_states() {
+        let filter = ContentFilter { safe_mode: true };
+        let general = set(ContentRating::General);
+        assert!(is_wanted(&general, &filter, "GameRunning", None));
+        assert!(is_wanted(&general, &filter, "MainMenu", None));
+        // Before entering a state that requires them
+        assert!(is_wanted(
+            &general,
+            &filter,
+            "SettingsMenu",
+            Some("GameRunning")
+        ));
+        assert!(!is_wanted(
+            &general,
+            &filter,
+            "SettingsMenu",
+            Some("MainMenu")
+        ));
+        assert!(!is_wanted(&general, &filter, "SettingsMenu", None));
+    }
+
+    #[test]
+    fn explicit_sets_are_never_wanted_in_safe_mode() {
+        let explicit = set(ContentRating::Explicit);
+        let safe = ContentFilter { safe_mode: true };
+        assert!(!is_wanted(&explicit, &safe, "GameRunning", None));
+        assert!(!is_wanted(
+            &explicit,
+            &safe,
+            "MainMenu",
+            Some("GameRunning")
+        ));
+        let unsafe_mode = ContentFilter { safe_mode: false };
+        assert_eq!(
+            is_wanted(&explicit, &unsafe_mode, "GameRunning", None),
+            !SAFE_BUILD
+        );
+    }
+}
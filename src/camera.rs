@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+use bevy::render::texture::ImageSampler;
+use bevy::render::view::RenderLayers;
+use bevy::window::{PrimaryWindow, WindowResized};
+
+use crate::settings::Settings;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+
+/// Size in pixels the game is drawn at before it is scaled up to the window,
+/// 30 by about 17 tiles of 16 pixels
+pub const VIEW_SIZE: UVec2 = UVec2::new(480, 270);
+
+// Layer of the scaled up game view, the game itself is on the default layer
+const SCREEN_LAYER: u8 = 1;
+
+/// Draws the game to an image at `VIEW_SIZE` and shows it in the window
+/// scaled up by the largest whole factor that fits, so pixels stay sharp.
+/// The menus are drawn by a camera of their own at the window resolution.
+pub struct PixelCameraPlugin;
+
+impl Plugin for PixelCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_cameras)
+            .add_systems(Update, fit_game_view);
+    }
+}
+
+/// The image the game is drawn to, and how many window pixels one of its
+/// pixels covers
+#[derive(Resource)]
+pub struct GameView {
+    pub image: Handle<Image>,
+    pub scale: u32,
+}
+
+/// Camera drawing the game to the `GameView` image
+#[derive(Component)]
+pub struct GameCamera;
+
+/// Camera drawing the menus to the window
+#[derive(Component)]
+pub struct UiCamera;
+
+// Sprite showing the game view in the window
+#[derive(Component)]
+struct GameCanvas;
+
+fn view_image(size: UVec2) -> Image {
+    let size = Extent3d {
+        width: size.x,
+        height: size.y,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("game_view"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        // Scaled up without blurring
+        sampler: ImageSampler::nearest(),
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+fn spawn_cameras(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = images.add(view_image(VIEW_SIZE));
+    commands.insert_resource(GameView {
+        image: image.clone(),
+        scale: 1,
+    });
+
+    commands.spawn((
+        GameCamera,
+        Camera2dBundle {
+            camera: Camera {
+                order: -1,
+                target: RenderTarget::Image(image.clone()),
+                clear_color: ClearColorConfig::Custom(BACKGROUND_COLOR),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+    // Shows the game view, with black bars where it does not cover the window
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                clear_color: ClearColorConfig::Custom(Color::BLACK),
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::layer(SCREEN_LAYER),
+    ));
+    commands.spawn((
+        GameCanvas,
+        SpriteBundle {
+            texture: image,
+            ..default()
+        },
+        RenderLayers::layer(SCREEN_LAYER),
+    ));
+    commands.spawn((
+        UiCamera,
+        IsDefaultUiCamera,
+        Camera2dBundle {
+            camera: Camera {
+                order: 1,
+                clear_color: ClearColorConfig::None,
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::none(),
+    ));
+}
+
+// Scale of the game view in window pixels and its size in game pixels. A
+// letterboxed view keeps `VIEW_SIZE`, otherwise it grows to fill the window.
+fn view_layout(window: UVec2, letterbox: bool) -> (u32, UVec2) {
+    let scale = (window.x / VIEW_SIZE.x).min(window.y / VIEW_SIZE.y).max(1);
+    let size = if letterbox {
+        VIEW_SIZE
+    } else {
+        // Never empty, textures need a size
+        ((window + UVec2::splat(scale - 1)) / scale).max(UVec2::ONE)
+    };
+    (scale, size)
+}
+
+/// Fit the game view to the window again when it is resized or the
+/// letterbox is switched
+fn fit_game_view(
+    settings: Res<Settings>,
+    mut resized_events: EventReader<WindowResized>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut game_view: ResMut<GameView>,
+    mut images: ResMut<Assets<Image>>,
+    mut canvas_query: Query<&mut Transform, With<GameCanvas>>,
+) {
+    let resized = resized_events.read().count() > 0;
+    if !resized && !settings.is_changed() {
+        return;
+    }
+    let (Ok(window), Ok(mut transform)) =
+        (window_query.get_single(), canvas_query.get_single_mut())
+    else {
+        return;
+    };
+
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+    // A minimised window has no size, the view is kept as it was
+    if window_size.cmpeq(UVec2::ZERO).any() {
+        return;
+    }
+    let (scale, size) = view_layout(window_size, settings.display.letterbox);
+    if let Some(image) = images.get_mut(&game_view.image) {
+        if image.size() != size {
+            image.resize(Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            });
+        }
+    }
+    if game_view.scale != scale {
+        game_view.scale = scale;
+    }
+
+    // Sprites are placed in window coordinates, odd leftovers are split on
+    // whole physical pixels
+    let scale_factor = window.scale_factor();
+    let leftover = (window_size.as_ivec2() - (size * scale).as_ivec2()).rem_euclid(IVec2::splat(2));
+    transform.scale = Vec3::splat(scale as f32 / scale_factor);
+    transform.translation = (leftover.as_vec2() * 0.5 / scale_factor).extend(0.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_view_takes_the_largest_whole_scale_that_fits() {
+        assert_eq!(view_layout(UVec2::new(1920, 1080), true), (4, VIEW_SIZE));
+        assert_eq!(view_layout(UVec2::new(1280, 720), true), (2, VIEW_SIZE));
+        // The shorter side decides
+        assert_eq!(view_layout(UVec2::new(3840, 600), true).0, 2);
+    }
+
+    #[test]
+    fn without_letterbox_the_view_fills_the_window() {
+        assert_eq!(
+            view_layout(UVec2::new(1920, 1080), false),
+            (4, UVec2::new(480, 270))
+        );
+        // Leftover window pixels get a game pixel of their own
+        assert_eq!(
+            view_layout(UVec2::new(1366, 768), false),
+            (2, UVec2::new(683, 384))
+        );
+        assert_eq!(
+            view_layout(UVec2::new(1001, 541), false),
+            (2, UVec2::new(501, 271))
+        );
+    }
+
+    #[test]
+    fn small_windows_are_drawn_at_scale_one() {
+        assert_eq!(view_layout(UVec2::new(320, 200), true), (1, VIEW_SIZE));
+        assert_eq!(
+            view_layout(UVec2::new(320, 200), false),
+            (1, UVec2::new(320, 200))
+        );
+    }
+
+    #[test]
+    fn an_empty_window_still_gets_a_view() {
+        assert_eq!(view_layout(UVec2::ZERO, false), (1, UVec2::ONE));
+        assert_eq!(
+            view_layout(UVec2::new(0, 720), false),
+            (1, UVec2::new(1, 720))
+        );
+        assert_eq!(view_layout(UVec2::ZERO, true), (1, VIEW_SIZE));
+    }
+}
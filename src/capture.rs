@@ -0,0 +1,337 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::utils::SystemTime;
+use bevy::window::{PrimaryWindow, WindowResolution};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use crate::gamestate::GameState;
+use crate::input::{ActionState, GameAction};
+use crate::persist;
+use crate::run::{RequestedSeed, StartRun};
+use crate::settings::{DisplayMode, Settings};
+use crate::transition::{ScreenTransitions, Transition, TransitionEffect};
+
+// Folder of the data directory screenshots are saved to
+const SCREENSHOT_DIR: &str = "screenshots";
+
+// Window size of a capture, the size the menus are laid out for
+const CAPTURE_RESOLUTION: (u32, u32) = (1920, 1080);
+
+// Frames a capture waits in its state for the layout and text to settle
+const SETTLE_FRAMES: u32 = 10;
+
+// A capture that has not reached its state by then gives up
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Saves a screenshot of the window on `GameAction::Screenshot`, and runs
+/// the `HeadlessCapture` when there is one
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, take_screenshot.run_if(screenshot_pressed))
+            .add_systems(
+                Startup,
+                skip_transitions.run_if(resource_exists::<HeadlessCapture>),
+            )
+            .add_systems(
+                Update,
+                run_capture.run_if(resource_exists::<HeadlessCapture>),
+            );
+    }
+}
+
+/// Runs the game in a hidden window with the default settings, goes to
+/// `state`, saves a screenshot of it to `path` and quits. Used for layout
+/// regression tests.
+#[derive(Resource, Clone, Debug)]
+pub struct HeadlessCapture {
+    state: GameState,
+    path: PathBuf,
+}
+
+impl HeadlessCapture {
+    /// A capture of the `GameState` named `state`
+    pub fn new(state: &str, path: impl Into<PathBuf>) -> Result<Self, String> {
+        Ok(Self {
+            state: state.parse()?,
+            path: path.into(),
+        })
+    }
+
+    /// The default settings in a window, so captures look the same on every
+    /// machine. They are never saved.
+    pub fn settings(&self) -> Settings {
+        let mut settings = Settings::default();
+        settings.display.mode = DisplayMode::Windowed;
+        settings.display.resolution = CAPTURE_RESOLUTION;
+        settings
+    }
+
+    /// Hidden window of the capture size, whatever the scale of the screen
+    pub fn window(&self) -> Window {
+        let (width, height) = CAPTURE_RESOLUTION;
+        let mut window = self.settings().window();
+        window.visible = false;
+        window.resolution =
+            WindowResolution::new(width as f32, height as f32).with_scale_factor_override(1.0);
+        window
+    }
+}
+
+fn screenshot_pressed(action_state: Res<ActionState>) -> bool {
+    action_state.just_pressed(GameAction::Screenshot)
+}
+
+/// Save the window to a PNG named after the time in the data directory,
+/// or download it on the web
+fn take_screenshot(
+    mut screenshots: ResMut<ScreenshotManager>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let name = format!("screenshot_{}.png", timestamp(SystemTime::now()));
+    let path = match persist::data_path(SCREENSHOT_DIR, &name) {
+        Ok(path) => path,
+        Err(error) => {
+            warn!("Could not save a screenshot: {error}");
+            return;
+        }
+    };
+    if screenshots.save_screenshot_to_disk(window, path).is_err() {
+        warn!("A screenshot of the window is being taken already");
+    }
+}
+
+// UTC time as `2024-05-17_13-04-59.250`, to sort and name files by
+fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (year, month, day) = civil_date((seconds / 86_400) as i64);
+    let (hour, minute, second) = (seconds / 3600 % 24, seconds / 60 % 60, seconds % 60);
+    format!(
+        "{year:04}-{month:02}-{day:02}_{hour:02}-{minute:02}-{second:02}.{:03}",
+        since_epoch.subsec_millis()
+    )
+}
+
+// Year, month and day of the day `days` after 1970-01-01
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    // Days counted from 0000-03-01 in 400 year eras, so leap days end a year
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = era * 400 + year_of_era + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// A capture shows the state itself, never a transition to it
+fn skip_transitions(mut transitions: ResMut<ScreenTransitions>) {
+    transitions.default = Transition {
+        effect: TransitionEffect::FadeToColor(Color::NONE),
+        duration: Duration::ZERO,
+    };
+}
+
+#[derive(Default)]
+struct CaptureProgress {
+    requested: bool,
+    settled: u32,
+    // Set to whether the screenshot could be written once it is done
+    saved: Option<Arc<OnceLock<bool>>>,
+}
+
+// What a capture does in a frame
+#[derive(Debug, PartialEq)]
+enum CaptureStep {
+    Wait,
+    StartRun,
+    Enter(GameState),
+    Screenshot,
+    GiveUp,
+}
+
+impl CaptureProgress {
+    // The step of this frame, in `state` after `elapsed` of running
+    fn step(&mut self, target: &GameState, state: &GameState, elapsed: Duration) -> CaptureStep {
+        if elapsed > CAPTURE_TIMEOUT {
+            return CaptureStep::GiveUp;
+        }
+        if state != target {
+            self.settled = 0;
+            if *state == GameState::AssetLoading || self.requested {
+                return CaptureStep::Wait;
+            }
+            self.requested = true;
+            // A run is started the usual way
+            return match target {
+                GameState::GameRunning => CaptureStep::StartRun,
+                _ => CaptureStep::Enter(target.clone()),
+            };
+        }
+        self.settled += 1;
+        if self.settled < SETTLE_FRAMES {
+            return CaptureStep::Wait;
+        }
+        CaptureStep::Screenshot
+    }
+}
+
+// Quit with a failing exit code, so a test running the capture fails
+fn fail_capture() -> ! {
+    std::process::exit(1);
+}
+
+/// Go to the captured state once the assets are loaded, save it once it
+/// settled and quit. The game exits with code 1 when the state is not
+/// reached in time or the file cannot be written.
+#[allow(clippy::too_many_arguments)]
+fn run_capture(
+    capture: Res<HeadlessCapture>,
+    mut progress: Local<CaptureProgress>,
+    game_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut start_events: EventWriter<StartRun>,
+    requested_seed: Option<Res<RequestedSeed>>,
+    time: Res<Time<Real>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    if let Some(saved) = progress.saved.as_ref() {
+        match saved.get() {
+            Some(true) => {
+                exit_events.send(AppExit);
+            }
+            Some(false) => fail_capture(),
+            None => {}
+        }
+        return;
+    }
+
+    match progress.step(&capture.state, game_state.get(), time.elapsed()) {
+        CaptureStep::Wait => {}
+        CaptureStep::StartRun => {
+            // With seed 0 unless another was asked for
+            let seed = requested_seed.map_or(0, |seed| seed.0);
+            start_events.send(StartRun { seed: Some(seed) });
+        }
+        CaptureStep::Enter(state) => next_state.set(state),
+        CaptureStep::GiveUp => {
+            error!("Could not reach {:?} to capture it", capture.state);
+            fail_capture();
+        }
+        CaptureStep::Screenshot => {
+            let Ok(window) = window_query.get_single() else {
+                return;
+            };
+            let saved = Arc::new(OnceLock::new());
+            let done = saved.clone();
+            let path = capture.path.clone();
+            let requested = screenshots.take_screenshot(window, move |image| {
+                let result = image
+                    .try_into_dynamic()
+                    .map_err(|error| error.to_string())
+                    .and_then(|image| {
+                        image
+                            .to_rgb8()
+                            .save(&path)
+                            .map_err(|error| error.to_string())
+                    });
+                match &result {
+                    Ok(()) => info!("Captured the screen to {}", path.display()),
+                    Err(error) => {
+                        error!("Could not save the capture to {}: {error}", path.display())
+                    }
+                }
+                let _ = done.set(result.is_ok());
+            });
+            // Taken next frame when another screenshot is on its way
+            if requested.is_ok() {
+                progress.saved = Some(saved);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_are_utc_dates_and_times() {
+        let at = |seconds: u64| timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds));
+        assert_eq!(at(0), "1970-01-01_00-00-00.000");
+        // A leap day, and the end of a century that is a leap year
+        assert_eq!(at(951_825_599), "2000-02-29_11-59-59.000");
+        assert_eq!(at(978_307_199), "2000-12-31_23-59-59.000");
+        assert_eq!(at(1_715_951_099), "2024-05-17_13-04-59.000");
+    }
+
+    #[test]
+    fn captures_wait_for_the_assets_and_request_their_state_once() {
+        let mut progress = CaptureProgress::default();
+        let target = GameState::GameOver;
+        let step = |progress: &mut CaptureProgress, state: &GameState| {
+            progress.step(&target, state, Duration::ZERO)
+        };
+        assert_eq!(
+            step(&mut progress, &GameState::AssetLoading),
+            CaptureStep::Wait
+        );
+        assert_eq!(
+            step(&mut progress, &GameState::MainMenu),
+            CaptureStep::Enter(GameState::GameOver)
+        );
+        // Until the state is entered
+        assert_eq!(step(&mut progress, &GameState::MainMenu), CaptureStep::Wait);
+        for _ in 1..SETTLE_FRAMES {
+            assert_eq!(step(&mut progress, &target), CaptureStep::Wait);
+        }
+        assert_eq!(step(&mut progress, &target), CaptureStep::Screenshot);
+    }
+
+    #[test]
+    fn the_game_is_captured_in_a_run_and_leaving_the_state_settles_again() {
+        let mut progress = CaptureProgress::default();
+        let target = GameState::GameRunning;
+        let mut step = |state: &GameState| progress.step(&target, state, Duration::ZERO);
+        assert_eq!(step(&GameState::MainMenu), CaptureStep::StartRun);
+        for _ in 1..SETTLE_FRAMES {
+            assert_eq!(step(&target), CaptureStep::Wait);
+        }
+        assert_eq!(step(&GameState::MainMenu), CaptureStep::Wait);
+        assert_eq!(step(&target), CaptureStep::Wait);
+    }
+
+    #[test]
+    fn captures_give_up_after_the_timeout() {
+        let mut progress = CaptureProgress::default();
+        let late = CAPTURE_TIMEOUT + Duration::from_secs(1);
+        assert_eq!(
+            progress.step(&GameState::MainMenu, &GameState::AssetLoading, late),
+            CaptureStep::GiveUp
+        );
+        assert_eq!(
+            progress.step(&GameState::MainMenu, &GameState::MainMenu, late),
+            CaptureStep::GiveUp
+        );
+    }
+}
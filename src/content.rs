@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Builds with the `safe` feature never show or load explicit content
+pub const SAFE_BUILD: bool = cfg!(feature = "safe");
+
+/// How explicit an asset, asset set or text of the manifest is
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ContentRating {
+    #[default]
+    General,
+    Explicit,
+}
+
+/// Content shown to the player. Safe mode replaces explicit assets and texts
+/// by their censored variants and skips explicit asset sets.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContentFilter {
+    pub safe_mode: bool,
+}
+
+impl Default for ContentFilter {
+    // Until the player accepted the disclaimer
+    fn default() -> Self {
+        Self { safe_mode: true }
+    }
+}
+
+impl ContentFilter {
+    pub fn allows(&self, rating: ContentRating) -> bool {
+        (!self.safe_mode && !SAFE_BUILD) || rating == ContentRating::General
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+    use bevy::asset::ron;
+
+    #[test]
+    fn safe_mode_allows_only_general_content() {
+        let filter = ContentFilter { safe_mode: true };
+        assert!(filter.allows(ContentRating::General));
+        assert!(!filter.allows(ContentRating::Explicit));
+        assert!(ContentFilter::default().safe_mode);
+    }
+
+    #[test]
+    fn safe_builds_ignore_safe_mode_from_the_settings_file() {
+        let settings: Settings = ron::from_str("(safe_mode: false)").unwrap();
+        let filter = ContentFilter {
+            safe_mode: settings.safe_mode,
+        };
+        assert!(filter.allows(ContentRating::General));
+        assert_eq!(filter.allows(ContentRating::Explicit), !SAFE_BUILD);
+    }
+}
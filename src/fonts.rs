@@ -0,0 +1,180 @@
+use ab_glyph::Font as _;
+use bevy::prelude::*;
+use bevy::ui::widget::measure_text_system;
+use bevy::utils::HashMap;
+use std::collections::BTreeSet;
+
+use crate::assetloader::{AssetLoadingState, AssetRegistry};
+use crate::locale::StringTable;
+use crate::ui::widgets::UiTheme;
+
+/// Keys of registry fonts, tried in order for every character. Bevy's
+/// built-in font is tried last.
+pub type FontStack = &'static [&'static str];
+
+/// Draws `FontStackText` with its font stack, and checks once the assets are
+/// loaded that the string tables can be drawn with the theme's fonts
+pub struct FontStackPlugin;
+
+impl Plugin for FontStackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            apply_font_stacks
+                .in_set(FontStackSystems)
+                .before(measure_text_system),
+        )
+        .add_systems(
+            OnEnter(AssetLoadingState::DoneLoading),
+            check_glyph_coverage,
+        );
+    }
+}
+
+/// Systems laying `FontStackText` out into `Text`, writers of the text run
+/// before them
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct FontStackSystems;
+
+/// Text drawn with a font stack. The text is written here instead of to the
+/// `Text`, which gets one section per run of characters drawn with the same
+/// font. The fonts of the sections are ignored.
+#[derive(Component, Clone, Debug)]
+pub struct FontStackText {
+    pub stack: FontStack,
+    pub sections: Vec<TextSection>,
+}
+
+// Fonts of a stack with the built-in font last, `None` until loaded
+fn stack_fonts<'a>(
+    stack: FontStack,
+    registry: &AssetRegistry,
+    fonts: &'a Assets<Font>,
+) -> Vec<(Handle<Font>, Option<&'a Font>)> {
+    stack
+        .iter()
+        .map(|key| registry.font(key))
+        .chain([Handle::default()])
+        .map(|handle| {
+            let font = fonts.get(&handle);
+            (handle, font)
+        })
+        .collect()
+}
+
+fn has_glyph(font: &Font, character: char) -> bool {
+    font.font.glyph_id(character).0 != 0
+}
+
+// Split the sections into runs of characters sharing the first font of the
+// stack that has their glyph. Whitespace stays in the run it is in.
+fn split_runs(
+    sections: &[TextSection],
+    stack: &[(Handle<Font>, Option<&Font>)],
+) -> Vec<TextSection> {
+    let mut runs = Vec::new();
+    for section in sections.iter() {
+        let mut run = String::new();
+        let mut run_font: Option<&Handle<Font>> = None;
+        for character in section.value.chars() {
+            let font = if character.is_whitespace() {
+                run_font
+            } else {
+                stack
+                    .iter()
+                    .find(|(_, font)| font.is_some_and(|font| has_glyph(font, character)))
+                    .map(|(handle, _)| handle)
+            };
+            // Characters no font has are drawn, as missing glyphs, with the
+            // first font
+            let font = font.unwrap_or(&stack[0].0);
+            if run_font.is_some_and(|run_font| run_font != font) {
+                runs.push(run_section(section, &mut run, run_font));
+            }
+            run_font = Some(font);
+            run.push(character);
+        }
+        runs.push(run_section(
+            section,
+            &mut run,
+            run_font.or(Some(&stack[0].0)),
+        ));
+    }
+    runs
+}
+
+fn run_section(
+    section: &TextSection,
+    run: &mut String,
+    font: Option<&Handle<Font>>,
+) -> TextSection {
+    TextSection {
+        value: std::mem::take(run),
+        style: TextStyle {
+            font: font.cloned().unwrap_or_default(),
+            ..section.style.clone()
+        },
+    }
+}
+
+/// Lay the changed texts out again, and every text once a font is loaded or
+/// the registry changed
+fn apply_font_stacks(
+    registry: Res<AssetRegistry>,
+    fonts: Res<Assets<Font>>,
+    mut font_events: EventReader<AssetEvent<Font>>,
+    mut text_query: Query<(Ref<FontStackText>, &mut Text)>,
+) {
+    let fonts_changed = font_events.read().count() > 0 || registry.is_changed();
+    for (stack_text, mut text) in &mut text_query {
+        if fonts_changed || stack_text.is_changed() {
+            let stack = stack_fonts(stack_text.stack, &registry, &fonts);
+            text.sections = split_runs(&stack_text.sections, &stack);
+        }
+    }
+}
+
+/// Warn about every character of the string tables that none of the fonts
+/// of the theme can draw
+fn check_glyph_coverage(
+    theme: Res<UiTheme>,
+    registry: Res<AssetRegistry>,
+    fonts: Res<Assets<Font>>,
+    tables: Res<Assets<StringTable>>,
+    asset_server: Res<AssetServer>,
+) {
+    let stacks = [theme.title_fonts, theme.text_fonts];
+    let stack_fonts: Vec<_> = stacks
+        .iter()
+        .flat_map(|stack| stack_fonts(stack, &registry, &fonts))
+        .filter_map(|(_, font)| font)
+        .collect();
+
+    for (id, table) in tables.iter() {
+        // Missing characters with the first key using them
+        let mut missing: HashMap<char, &str> = HashMap::new();
+        for (key, value) in table.iter() {
+            for character in value.chars().filter(|character| !character.is_whitespace()) {
+                if !stack_fonts.iter().any(|font| has_glyph(font, character)) {
+                    missing.entry(character).or_insert(key);
+                }
+            }
+        }
+        if missing.is_empty() {
+            continue;
+        }
+        let characters: BTreeSet<_> = missing
+            .iter()
+            .map(|(character, key)| format!("{character:?} ({key})"))
+            .collect();
+        let path = asset_server
+            .get_path(id)
+            .map(|path| path.to_string())
+            .unwrap_or_default();
+        warn!(
+            "No font can draw {} characters of {path}: {}",
+            characters.len(),
+            characters.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+}
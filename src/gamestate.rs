@@ -1,3 +1,4 @@
+use bevy::ecs::schedule::{apply_state_transition, StateTransitionEvent};
 use bevy::prelude::*;
 
 // Game loading states
@@ -8,13 +9,275 @@ pub enum GameState {
     DisclaimerMenu,
     MainMenu,
     GameRunning,
-    //    PausedMenu,
+    GameOver,
+    Victory,
+}
+
+impl std::str::FromStr for GameState {
+    type Err = String;
+
+    /// The state named as in the enum, `MainMenu` for `GameState::MainMenu`
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "AssetLoading" => Ok(GameState::AssetLoading),
+            "DisclaimerMenu" => Ok(GameState::DisclaimerMenu),
+            "MainMenu" => Ok(GameState::MainMenu),
+            "GameRunning" => Ok(GameState::GameRunning),
+            "GameOver" => Ok(GameState::GameOver),
+            "Victory" => Ok(GameState::Victory),
+            _ => Err(format!("There is no game state {name}")),
+        }
+    }
+}
+
+// Main menu screens, `Closed` outside of `GameState::MainMenu`
+#[derive(States, Debug, Hash, Default, Eq, PartialEq, Clone, Copy)]
+pub enum MainMenuState {
+    #[default]
+    Closed,
+    Main,
+    Settings,
+    Bindings,
+}
+
+// Pause menu states, the game stays in `GameState::GameRunning` while paused
+#[derive(States, Debug, Hash, Default, Eq, PartialEq, Clone, Copy)]
+pub enum PauseMenuState {
+    #[default]
+    Unpaused,
+    Main,
+    Inventory,
+    Skills,
+    Save,
+    Map,
 }
 
 pub struct GameStatePlugin;
 
 impl Plugin for GameStatePlugin {
     fn build(&self, app: &mut App) {
-        app.init_state::<GameState>();
+        app.init_state::<GameState>()
+            .init_state::<MainMenuState>()
+            .init_state::<PauseMenuState>()
+            .add_despawn_on_exit::<GameState>()
+            .add_despawn_on_exit::<MainMenuState>()
+            .add_despawn_on_exit::<PauseMenuState>()
+            .add_systems(OnEnter(GameState::MainMenu), open_main_menu)
+            .add_systems(OnExit(GameState::MainMenu), close_main_menu)
+            .add_systems(OnExit(PauseMenuState::Unpaused), pause_virtual_time)
+            .add_systems(OnEnter(PauseMenuState::Unpaused), unpause_virtual_time)
+            .add_systems(OnExit(GameState::GameRunning), close_pause_menu);
+    }
+}
+
+fn open_main_menu(mut next_main_menu_state: ResMut<NextState<MainMenuState>>) {
+    next_main_menu_state.set(MainMenuState::Main);
+}
+
+fn close_main_menu(mut next_main_menu_state: ResMut<NextState<MainMenuState>>) {
+    next_main_menu_state.set(MainMenuState::Closed);
+}
+
+// Gameplay systems run on virtual time, which stands still while paused
+fn pause_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unpause_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+// Leaving the game from the pause menu must not leave it paused
+fn close_pause_menu(mut next_pause_state: ResMut<NextState<PauseMenuState>>) {
+    next_pause_state.set(PauseMenuState::Unpaused);
+}
+
+/// Entities tagged with `DespawnOnExit(state)` are despawned, with their
+/// children, when `state` is exited. Tag the root of a UI tree or a gameplay
+/// entity instead of writing a despawn system for it.
+#[derive(Component, Debug, Clone)]
+pub struct DespawnOnExit<S: States>(pub S);
+
+pub trait AppDespawnOnExitExt {
+    /// Despawn the entities tagged with `DespawnOnExit<S>` on exit of their
+    /// state, the state `S` must be initialized on the app
+    fn add_despawn_on_exit<S: States>(&mut self) -> &mut Self;
+}
+
+impl AppDespawnOnExitExt for App {
+    fn add_despawn_on_exit<S: States>(&mut self) -> &mut Self {
+        self.add_systems(
+            StateTransition,
+            despawn_on_exit::<S>.after(apply_state_transition::<S>),
+        )
+    }
+}
+
+fn despawn_on_exit<S: States>(
+    mut commands: Commands,
+    mut transitions: EventReader<StateTransitionEvent<S>>,
+    scoped_query: Query<(Entity, &DespawnOnExit<S>)>,
+) {
+    for transition in transitions.read() {
+        for (entity, scope) in scoped_query.iter() {
+            if scope.0 == transition.before {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assetloader::AssetLoadingState;
+
+    const GAME_STATES: [GameState; 6] = [
+        GameState::AssetLoading,
+        GameState::DisclaimerMenu,
+        GameState::MainMenu,
+        GameState::GameRunning,
+        GameState::GameOver,
+        GameState::Victory,
+    ];
+
+    const ASSET_LOADING_STATES: [AssetLoadingState; 5] = [
+        AssetLoadingState::NotLoaded,
+        AssetLoadingState::LoadingManifest,
+        AssetLoadingState::Loading,
+        AssetLoadingState::DoneLoading,
+        AssetLoadingState::FailedLoading,
+    ];
+
+    const MAIN_MENU_STATES: [MainMenuState; 4] = [
+        MainMenuState::Closed,
+        MainMenuState::Main,
+        MainMenuState::Settings,
+        MainMenuState::Bindings,
+    ];
+
+    const PAUSE_MENU_STATES: [PauseMenuState; 6] = [
+        PauseMenuState::Unpaused,
+        PauseMenuState::Main,
+        PauseMenuState::Inventory,
+        PauseMenuState::Skills,
+        PauseMenuState::Save,
+        PauseMenuState::Map,
+    ];
+
+    // Spawn a tagged root with a child, like a menu
+    fn spawn_scoped_tree<S: States>(state: S) -> impl Fn(Commands) {
+        move |mut commands: Commands| {
+            commands
+                .spawn(DespawnOnExit(state.clone()))
+                .with_children(|parent| {
+                    parent.spawn_empty();
+                });
+        }
+    }
+
+    fn add_scoped_trees<S: States>(app: &mut App, states: &[S]) {
+        for state in states {
+            app.add_systems(OnEnter(state.clone()), spawn_scoped_tree(state.clone()));
+        }
+    }
+
+    // App with a tagged tree spawned on entering every state
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(GameStatePlugin)
+            .init_state::<AssetLoadingState>()
+            .add_despawn_on_exit::<AssetLoadingState>();
+        add_scoped_trees(&mut app, &GAME_STATES);
+        add_scoped_trees(&mut app, &ASSET_LOADING_STATES);
+        add_scoped_trees(&mut app, &MAIN_MENU_STATES);
+        add_scoped_trees(&mut app, &PAUSE_MENU_STATES);
+        app.update();
+        app
+    }
+
+    fn set_state<S: States>(app: &mut App, state: S) {
+        app.world.resource_mut::<NextState<S>>().set(state);
+        app.update();
+    }
+
+    fn scoped_states<S: States>(app: &mut App) -> Vec<S> {
+        app.world
+            .query::<&DespawnOnExit<S>>()
+            .iter(&app.world)
+            .map(|scope| scope.0.clone())
+            .collect()
+    }
+
+    // Cycle through every state a few times, only the tree of the current
+    // state may be left each time
+    fn assert_cycle_leaves_no_entities<S: States>(states: &[S]) {
+        let mut app = app();
+        let entity_count = app.world.entities().len();
+        assert_eq!(scoped_states::<S>(&mut app), vec![states[0].clone()]);
+
+        for state in states.iter().cycle().skip(1).take(states.len() * 3) {
+            set_state(&mut app, state.clone());
+            assert_eq!(scoped_states::<S>(&mut app), vec![state.clone()]);
+            assert_eq!(app.world.entities().len(), entity_count);
+        }
+    }
+
+    #[test]
+    fn game_states_despawn_their_entities_on_exit() {
+        assert_cycle_leaves_no_entities(&GAME_STATES);
+    }
+
+    #[test]
+    fn asset_loading_states_despawn_their_entities_on_exit() {
+        assert_cycle_leaves_no_entities(&ASSET_LOADING_STATES);
+    }
+
+    #[test]
+    fn main_menu_states_despawn_their_entities_on_exit() {
+        assert_cycle_leaves_no_entities(&MAIN_MENU_STATES);
+    }
+
+    #[test]
+    fn pause_menu_states_despawn_their_entities_on_exit() {
+        assert_cycle_leaves_no_entities(&PAUSE_MENU_STATES);
+    }
+
+    #[test]
+    fn leaving_the_game_while_paused_despawns_the_pause_menu() {
+        let mut app = app();
+        let entity_count = app.world.entities().len();
+        set_state(&mut app, GameState::GameRunning);
+        set_state(&mut app, PauseMenuState::Inventory);
+
+        set_state(&mut app, GameState::MainMenu);
+        app.update();
+        assert_eq!(
+            scoped_states::<PauseMenuState>(&mut app),
+            vec![PauseMenuState::Unpaused]
+        );
+        assert_eq!(app.world.entities().len(), entity_count);
+    }
+
+    #[test]
+    fn leaving_the_main_menu_despawns_its_screens() {
+        let mut app = app();
+        let entity_count = app.world.entities().len();
+        set_state(&mut app, GameState::MainMenu);
+        app.update();
+        assert_eq!(
+            scoped_states::<MainMenuState>(&mut app),
+            vec![MainMenuState::Main]
+        );
+        set_state(&mut app, MainMenuState::Bindings);
+
+        set_state(&mut app, GameState::GameRunning);
+        app.update();
+        assert_eq!(
+            scoped_states::<MainMenuState>(&mut app),
+            vec![MainMenuState::Closed]
+        );
+        assert_eq!(app.world.entities().len(), entity_count);
     }
 }
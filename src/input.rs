@@ -0,0 +1,478 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+use bevy::utils::HashSet;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::f32::consts::FRAC_PI_4;
+
+use crate::locale::{tr, Localized, Strings};
+
+/// Turns the keyboard, mouse, gamepad and touch input into `GameAction`s.
+/// Gameplay reads `ActionState` and never the devices themselves.
+pub struct GameInputPlugin;
+
+impl Plugin for GameInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputBindings>()
+            .init_resource::<ActionState>()
+            .add_systems(
+                PreUpdate,
+                update_action_state
+                    .in_set(GameInputSet)
+                    .after(InputSystem)
+                    // Screen transitions block input before the UI reads it
+                    .after(UiSystem::Focus),
+            );
+    }
+}
+
+/// Systems updating `ActionState`
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct GameInputSet;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GameAction {
+    MoveNorth,
+    MoveNorthEast,
+    MoveEast,
+    MoveSouthEast,
+    MoveSouth,
+    MoveSouthWest,
+    MoveWest,
+    MoveNorthWest,
+    Wait,
+    PickUp,
+    Use,
+    Descend,
+    Inventory,
+    Pause,
+    Screenshot,
+}
+
+// Moves clockwise from north, the order touches are matched in
+const MOVES: [GameAction; 8] = [
+    GameAction::MoveNorth,
+    GameAction::MoveNorthEast,
+    GameAction::MoveEast,
+    GameAction::MoveSouthEast,
+    GameAction::MoveSouth,
+    GameAction::MoveSouthWest,
+    GameAction::MoveWest,
+    GameAction::MoveNorthWest,
+];
+
+impl GameAction {
+    pub const ALL: [GameAction; 15] = [
+        GameAction::MoveNorth,
+        GameAction::MoveNorthEast,
+        GameAction::MoveEast,
+        GameAction::MoveSouthEast,
+        GameAction::MoveSouth,
+        GameAction::MoveSouthWest,
+        GameAction::MoveWest,
+        GameAction::MoveNorthWest,
+        GameAction::Wait,
+        GameAction::PickUp,
+        GameAction::Use,
+        GameAction::Descend,
+        GameAction::Inventory,
+        GameAction::Pause,
+        GameAction::Screenshot,
+    ];
+
+    /// Name shown to the player
+    pub fn name(self) -> Localized {
+        match self {
+            GameAction::MoveNorth => tr("action.move_north"),
+            GameAction::MoveNorthEast => tr("action.move_north_east"),
+            GameAction::MoveEast => tr("action.move_east"),
+            GameAction::MoveSouthEast => tr("action.move_south_east"),
+            GameAction::MoveSouth => tr("action.move_south"),
+            GameAction::MoveSouthWest => tr("action.move_south_west"),
+            GameAction::MoveWest => tr("action.move_west"),
+            GameAction::MoveNorthWest => tr("action.move_north_west"),
+            GameAction::Wait => tr("action.wait"),
+            GameAction::PickUp => tr("action.pick_up"),
+            GameAction::Use => tr("action.use"),
+            GameAction::Descend => tr("action.descend"),
+            GameAction::Inventory => tr("action.inventory"),
+            GameAction::Pause => tr("action.pause"),
+            GameAction::Screenshot => tr("action.screenshot"),
+        }
+    }
+}
+
+/// A key or button triggering an action
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+impl InputBinding {
+    /// Name shown to the player
+    pub fn label(&self, strings: &Strings) -> String {
+        let mouse = |button: &str| strings.format(&tr("input.mouse").arg("button", button));
+        let gamepad = |button: &str| strings.format(&tr("input.gamepad").arg("button", button));
+        match self {
+            InputBinding::Key(key) => {
+                let name = format!("{key:?}");
+                // `KeyA` and `Digit1` are shown as `A` and `1`
+                match name.strip_prefix("Key").or(name.strip_prefix("Digit")) {
+                    Some(short) => String::from(short),
+                    None => name,
+                }
+            }
+            InputBinding::Mouse(MouseButton::Left) => String::from(strings.get("input.mouse_left")),
+            InputBinding::Mouse(MouseButton::Right) => {
+                String::from(strings.get("input.mouse_right"))
+            }
+            InputBinding::Mouse(MouseButton::Middle) => {
+                String::from(strings.get("input.mouse_middle"))
+            }
+            InputBinding::Mouse(button) => mouse(&format!("{button:?}")),
+            InputBinding::Gamepad(GamepadButtonType::South) => gamepad("A"),
+            InputBinding::Gamepad(GamepadButtonType::East) => gamepad("B"),
+            InputBinding::Gamepad(GamepadButtonType::West) => gamepad("X"),
+            InputBinding::Gamepad(GamepadButtonType::North) => gamepad("Y"),
+            InputBinding::Gamepad(button) => gamepad(&format!("{button:?}")),
+        }
+    }
+}
+
+/// The inputs bound to every action, kept in the `Settings`
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(
+    from = "BTreeMap<GameAction, Vec<InputBinding>>",
+    into = "BTreeMap<GameAction, Vec<InputBinding>>"
+)]
+pub struct InputBindings(BTreeMap<GameAction, Vec<InputBinding>>);
+
+impl From<InputBindings> for BTreeMap<GameAction, Vec<InputBinding>> {
+    // Saved as the map alone, the way it is read back
+    fn from(bindings: InputBindings) -> Self {
+        bindings.0
+    }
+}
+
+impl From<BTreeMap<GameAction, Vec<InputBinding>>> for InputBindings {
+    /// Actions added since the bindings were saved get the default inputs
+    /// no other action took
+    fn from(mut saved: BTreeMap<GameAction, Vec<InputBinding>>) -> Self {
+        let bound: Vec<InputBinding> = saved.values().flatten().copied().collect();
+        for (action, mut bindings) in Self::default().0 {
+            saved.entry(action).or_insert_with(|| {
+                bindings.retain(|binding| !bound.contains(binding));
+                bindings
+            });
+        }
+        Self(saved)
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: GameAction) -> &[InputBinding] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Bind `binding` to `action`, taking it away from any other action
+    pub fn bind(&mut self, action: GameAction, binding: InputBinding) {
+        for bindings in self.0.values_mut() {
+            bindings.retain(|bound| *bound != binding);
+        }
+        self.0.entry(action).or_default().push(binding);
+    }
+
+    /// Leave `action` unbound, it is saved without inputs
+    pub fn clear(&mut self, action: GameAction) {
+        self.0.insert(action, Vec::new());
+    }
+}
+
+impl Default for InputBindings {
+    /// Vi-keys, the numpad and the arrow keys with Home, End, Page Up and
+    /// Page Down for the diagonals
+    fn default() -> Self {
+        use GamepadButtonType as Pad;
+        use InputBinding::{Gamepad, Key, Mouse};
+
+        Self(BTreeMap::from([
+            (
+                GameAction::MoveNorth,
+                vec![
+                    Key(KeyCode::KeyK),
+                    Key(KeyCode::Numpad8),
+                    Key(KeyCode::ArrowUp),
+                    Gamepad(Pad::DPadUp),
+                ],
+            ),
+            (
+                GameAction::MoveNorthEast,
+                vec![
+                    Key(KeyCode::KeyU),
+                    Key(KeyCode::Numpad9),
+                    Key(KeyCode::PageUp),
+                ],
+            ),
+            (
+                GameAction::MoveEast,
+                vec![
+                    Key(KeyCode::KeyL),
+                    Key(KeyCode::Numpad6),
+                    Key(KeyCode::ArrowRight),
+                    Gamepad(Pad::DPadRight),
+                ],
+            ),
+            (
+                GameAction::MoveSouthEast,
+                vec![
+                    Key(KeyCode::KeyN),
+                    Key(KeyCode::Numpad3),
+                    Key(KeyCode::PageDown),
+                ],
+            ),
+            (
+                GameAction::MoveSouth,
+                vec![
+                    Key(KeyCode::KeyJ),
+                    Key(KeyCode::Numpad2),
+                    Key(KeyCode::ArrowDown),
+                    Gamepad(Pad::DPadDown),
+                ],
+            ),
+            (
+                GameAction::MoveSouthWest,
+                vec![Key(KeyCode::KeyB), Key(KeyCode::Numpad1), Key(KeyCode::End)],
+            ),
+            (
+                GameAction::MoveWest,
+                vec![
+                    Key(KeyCode::KeyH),
+                    Key(KeyCode::Numpad4),
+                    Key(KeyCode::ArrowLeft),
+                    Gamepad(Pad::DPadLeft),
+                ],
+            ),
+            (
+                GameAction::MoveNorthWest,
+                vec![
+                    Key(KeyCode::KeyY),
+                    Key(KeyCode::Numpad7),
+                    Key(KeyCode::Home),
+                ],
+            ),
+            (
+                GameAction::Wait,
+                vec![
+                    Key(KeyCode::Period),
+                    Key(KeyCode::Numpad5),
+                    Mouse(MouseButton::Right),
+                    Gamepad(Pad::West),
+                ],
+            ),
+            (
+                GameAction::PickUp,
+                vec![Key(KeyCode::KeyG), Key(KeyCode::Comma), Gamepad(Pad::South)],
+            ),
+            (
+                GameAction::Use,
+                vec![Key(KeyCode::KeyA), Gamepad(Pad::East)],
+            ),
+            (
+                GameAction::Descend,
+                vec![
+                    Key(KeyCode::Enter),
+                    Key(KeyCode::NumpadEnter),
+                    Gamepad(Pad::RightTrigger),
+                ],
+            ),
+            (
+                GameAction::Inventory,
+                vec![Key(KeyCode::KeyI), Key(KeyCode::Tab), Gamepad(Pad::North)],
+            ),
+            (
+                GameAction::Pause,
+                vec![Key(KeyCode::Escape), Gamepad(Pad::Start)],
+            ),
+            (GameAction::Screenshot, vec![Key(KeyCode::F12)]),
+        ]))
+    }
+}
+
+/// Actions started this frame
+#[derive(Resource, Default)]
+pub struct ActionState {
+    just_pressed: HashSet<GameAction>,
+}
+
+impl ActionState {
+    pub fn just_pressed(&self, action: GameAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_action_state(
+    bindings: Res<InputBindings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    touches: Res<Touches>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut action_state: ResMut<ActionState>,
+) {
+    action_state.just_pressed.clear();
+
+    let just_pressed = |binding: &InputBinding| match *binding {
+        InputBinding::Key(key) => keys.just_pressed(key),
+        InputBinding::Mouse(button) => mouse_buttons.just_pressed(button),
+        InputBinding::Gamepad(button_type) => gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type))),
+    };
+    for (action, action_bindings) in bindings.0.iter() {
+        if action_bindings.iter().any(just_pressed) {
+            action_state.just_pressed.insert(*action);
+        }
+    }
+
+    // Touches are not rebound: a tap moves towards it from the centre of the
+    // screen, where the player stands, or waits on the player. Tapping with
+    // a second finger pauses.
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let size = Vec2::new(window.width(), window.height());
+    for touch in touches.iter_just_pressed() {
+        let action = if touches.iter().count() > 1 {
+            GameAction::Pause
+        } else {
+            touch_action(touch.position(), size)
+        };
+        action_state.just_pressed.insert(action);
+    }
+}
+
+// Action of a tap at `position` in a window of `size`
+fn touch_action(position: Vec2, size: Vec2) -> GameAction {
+    let offset = position - size / 2.0;
+    if offset.length() < 0.1 * size.min_element() {
+        return GameAction::Wait;
+    }
+    // Clockwise from north, window coordinates grow downwards
+    let angle = offset.x.atan2(-offset.y);
+    let sector = (angle / FRAC_PI_4).round() as i32;
+    MOVES[sector.rem_euclid(MOVES.len() as i32) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::input::touch::{TouchInput, TouchPhase};
+    use bevy::input::InputPlugin;
+
+    #[test]
+    fn binding_an_input_takes_it_from_its_action() {
+        let mut bindings = InputBindings::default();
+        let f12 = InputBinding::Key(KeyCode::F12);
+        bindings.bind(GameAction::Wait, f12);
+        assert_eq!(bindings.get(GameAction::Screenshot), &[]);
+        assert_eq!(bindings.get(GameAction::Wait).last(), Some(&f12));
+
+        // Binding it again does not add it twice
+        bindings.bind(GameAction::Wait, f12);
+        let count = bindings
+            .get(GameAction::Wait)
+            .iter()
+            .filter(|b| **b == f12)
+            .count();
+        assert_eq!(count, 1);
+
+        bindings.clear(GameAction::Wait);
+        assert_eq!(bindings.get(GameAction::Wait), &[]);
+    }
+
+    #[test]
+    fn actions_missing_from_saved_bindings_get_the_free_defaults() {
+        let mut saved: BTreeMap<GameAction, Vec<InputBinding>> = InputBindings::default().into();
+        saved.remove(&GameAction::Screenshot);
+        saved.remove(&GameAction::Inventory);
+        saved.insert(GameAction::Wait, vec![InputBinding::Key(KeyCode::Tab)]);
+
+        let bindings = InputBindings::from(saved);
+        let defaults = InputBindings::default();
+        assert_eq!(
+            bindings.get(GameAction::Screenshot),
+            defaults.get(GameAction::Screenshot)
+        );
+        // Tab stays with the action it was saved on
+        assert_eq!(
+            bindings.get(GameAction::Inventory),
+            &[
+                InputBinding::Key(KeyCode::KeyI),
+                InputBinding::Gamepad(GamepadButtonType::North)
+            ]
+        );
+        assert_eq!(
+            bindings.get(GameAction::Wait),
+            &[InputBinding::Key(KeyCode::Tab)]
+        );
+    }
+
+    #[test]
+    fn taps_move_towards_them_or_wait_in_the_centre() {
+        let size = Vec2::new(800.0, 600.0);
+        let centre = size / 2.0;
+        assert_eq!(touch_action(centre, size), GameAction::Wait);
+        assert_eq!(
+            touch_action(centre + Vec2::new(30.0, -30.0), size),
+            GameAction::Wait
+        );
+
+        // Window coordinates grow downwards
+        let directions = [
+            (Vec2::new(0.0, -1.0), GameAction::MoveNorth),
+            (Vec2::new(1.0, -1.0), GameAction::MoveNorthEast),
+            (Vec2::new(1.0, 0.0), GameAction::MoveEast),
+            (Vec2::new(1.0, 1.0), GameAction::MoveSouthEast),
+            (Vec2::new(0.0, 1.0), GameAction::MoveSouth),
+            (Vec2::new(-1.0, 1.0), GameAction::MoveSouthWest),
+            (Vec2::new(-1.0, 0.0), GameAction::MoveWest),
+            (Vec2::new(-1.0, -1.0), GameAction::MoveNorthWest),
+        ];
+        for (direction, action) in directions {
+            assert_eq!(touch_action(centre + direction * 200.0, size), action);
+            // Anywhere within the 45 degree sector
+            let turned = Vec2::from_angle(0.3).rotate(direction);
+            assert_eq!(touch_action(centre + turned * 200.0, size), action);
+        }
+    }
+
+    #[test]
+    fn a_second_finger_pauses() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin, GameInputPlugin));
+        let window = app.world.spawn((Window::default(), PrimaryWindow)).id();
+        let mut tap = |id, position| {
+            app.world.send_event(TouchInput {
+                phase: TouchPhase::Started,
+                position,
+                window,
+                force: None,
+                id,
+            });
+            app.update();
+            let action_state = app.world.resource::<ActionState>();
+            GameAction::ALL
+                .into_iter()
+                .filter(|action| action_state.just_pressed(*action))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tap(0, Vec2::new(640.0, 360.0)), vec![GameAction::Wait]);
+        assert_eq!(tap(1, Vec2::new(640.0, 0.0)), vec![GameAction::Pause]);
+    }
+}
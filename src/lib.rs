@@ -3,17 +3,50 @@ use bevy::prelude::*;
 
 // Plugin crates
 mod assetloader;
+mod camera;
+mod capture;
+mod content;
+mod fonts;
 mod gamestate;
+mod input;
+mod locale;
+mod map;
+mod persist;
+mod rng;
+mod run;
+mod settings;
+mod transition;
 mod ui;
 mod window;
 
 // Use declarations
+use assetloader::hotreload::HotReloadPlugin;
+use assetloader::sets::AssetSetPlugin;
 use assetloader::AssetLoaderPlugin;
+use camera::PixelCameraPlugin;
+use capture::CapturePlugin;
+use fonts::FontStackPlugin;
 use gamestate::GameStatePlugin;
+use input::GameInputPlugin;
+use locale::LocalePlugin;
+use map::MapPlugin;
+use run::RunPlugin;
+use settings::SettingsPlugin;
+use transition::ScreenTransitionPlugin;
+use ui::bindingsmenu::BindingsMenuPlugin;
 use ui::disclaimermenu::DisclaimerMenuPlugin;
+use ui::loadfailedmenu::LoadFailedMenuPlugin;
 use ui::mainmenu::MainMenuPlugin;
+use ui::pausemenu::PauseMenuPlugin;
+use ui::runendmenu::RunEndMenuPlugin;
+use ui::settingsmenu::SettingsMenuPlugin;
+use ui::widgets::WidgetsPlugin;
 use window::WindowPlugin;
 
+pub use capture::HeadlessCapture;
+pub use run::RequestedSeed;
+pub use settings::Settings;
+
 #[cfg(debug_assertions)]
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 
@@ -21,11 +54,28 @@ pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(AssetLoaderPlugin)
+        app.add_plugins(SettingsPlugin)
+            .add_plugins(AssetLoaderPlugin)
+            .add_plugins(WidgetsPlugin)
+            .add_plugins(AssetSetPlugin)
+            .add_plugins(HotReloadPlugin)
             .add_plugins(MainMenuPlugin)
+            .add_plugins(SettingsMenuPlugin)
+            .add_plugins(BindingsMenuPlugin)
             .add_plugins(DisclaimerMenuPlugin)
+            .add_plugins(LoadFailedMenuPlugin)
+            .add_plugins(PauseMenuPlugin)
+            .add_plugins(RunEndMenuPlugin)
             .add_plugins(WindowPlugin)
-            .add_plugins(GameStatePlugin);
+            .add_plugins(PixelCameraPlugin)
+            .add_plugins(CapturePlugin)
+            .add_plugins(GameStatePlugin)
+            .add_plugins(GameInputPlugin)
+            .add_plugins(LocalePlugin)
+            .add_plugins(FontStackPlugin)
+            .add_plugins(RunPlugin)
+            .add_plugins(MapPlugin)
+            .add_plugins(ScreenTransitionPlugin);
 
         #[cfg(debug_assertions)]
         {
@@ -0,0 +1,348 @@
+use bevy::asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext, LoadedUntypedAsset};
+use bevy::prelude::*;
+use bevy::utils::{BoxedFuture, HashMap};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::assetloader::{AssetRegistry, RatedTexts};
+use crate::content::ContentFilter;
+use crate::fonts::{FontStackSystems, FontStackText};
+
+/// Shows every `Localized` text in the language of the `Locale`, and shows
+/// it again when the language or a string table changes
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .init_resource::<Strings>()
+            .init_asset::<StringTable>()
+            .init_asset_loader::<StringTableLoader>()
+            .add_systems(
+                PostUpdate,
+                (update_strings, localize_texts)
+                    .chain()
+                    .before(FontStackSystems),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Language {
+    #[default]
+    SimplifiedChinese,
+    English,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::SimplifiedChinese, Language::English];
+
+    /// Strings missing from a table are taken from this language
+    pub const FALLBACK: Language = Language::SimplifiedChinese;
+
+    /// Language tag, naming the string table in `assets/locales`
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::SimplifiedChinese => "zh-Hans",
+            Language::English => "en",
+        }
+    }
+
+    /// Name of the language written in itself
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::SimplifiedChinese => "简体中文",
+            Language::English => "English",
+        }
+    }
+
+    // Key of the string table in the asset registry
+    fn table_key(self) -> String {
+        format!("strings.{}", self.code())
+    }
+}
+
+/// The language the game is shown in, kept in the `Settings`
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Locale {
+    pub language: Language,
+}
+
+/// Strings of one language by key, read from a `.strings.ron` file
+#[derive(Asset, TypePath, Deserialize, Debug, Default)]
+#[serde(transparent)]
+pub struct StringTable(HashMap<String, String>);
+
+impl StringTable {
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.0.iter()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StringTableLoaderError {
+    #[error("could not read string table: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse string table: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+#[derive(Default)]
+pub struct StringTableLoader;
+
+impl AssetLoader for StringTableLoader {
+    type Asset = StringTable;
+    type Settings = ();
+    type Error = StringTableLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<StringTable>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["strings.ron"]
+    }
+}
+
+/// The strings of the current language, filled in from the fallback
+/// language where its table misses a key
+#[derive(Resource, Default)]
+pub struct Strings(HashMap<String, String>);
+
+impl Strings {
+    fn new(table: Option<&StringTable>, fallback: Option<&StringTable>) -> Self {
+        let mut strings = HashMap::new();
+        for table in [fallback, table].into_iter().flatten() {
+            strings.extend(
+                table
+                    .0
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            );
+        }
+        Self(strings)
+    }
+
+    // Show the string of `censored`, or the generic notice, for `key`
+    fn censor(&mut self, key: &str, censored: Option<&str>) {
+        let value = String::from(self.get(censored.unwrap_or("content.censored")));
+        self.0.insert(String::from(key), value);
+    }
+
+    /// Whether no string table is loaded yet
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The string of `key`, or the key itself when no table has it
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        match self.0.get(key) {
+            Some(value) => value,
+            None => {
+                warn!("No string for key `{key}`");
+                key
+            }
+        }
+    }
+
+    /// The string of the text with its `{name}` arguments filled in
+    pub fn format(&self, text: &Localized) -> String {
+        let mut value = String::from(self.get(&text.key));
+        for (name, arg) in text.args.iter() {
+            value = value.replace(&format!("{{{name}}}"), arg);
+        }
+        value
+    }
+}
+
+/// Text looked up by its key in the string tables, shown again in the new
+/// language when it changes
+#[derive(Component, Clone, Debug)]
+pub struct Localized {
+    pub key: String,
+    pub args: Vec<(&'static str, String)>,
+}
+
+/// The text of the string table entry `key`
+pub fn tr(key: &str) -> Localized {
+    Localized {
+        key: String::from(key),
+        args: Vec::new(),
+    }
+}
+
+impl Localized {
+    /// Fill `{name}` in with `value`
+    pub fn arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+}
+
+/// Text given to a widget, either looked up in the string tables or shown
+/// as it is
+#[derive(Clone, Debug)]
+pub enum UiText {
+    Localized(Localized),
+    Raw(String),
+}
+
+impl From<Localized> for UiText {
+    fn from(localized: Localized) -> Self {
+        UiText::Localized(localized)
+    }
+}
+
+impl From<&str> for UiText {
+    fn from(value: &str) -> Self {
+        UiText::Raw(String::from(value))
+    }
+}
+
+impl From<String> for UiText {
+    fn from(value: String) -> Self {
+        UiText::Raw(value)
+    }
+}
+
+/// Build the strings again once the tables are loaded or reloaded, or the
+/// language or the content filter changed
+#[allow(clippy::too_many_arguments)]
+fn update_strings(
+    locale: Res<Locale>,
+    filter: Res<ContentFilter>,
+    rated_texts: Res<RatedTexts>,
+    registry: Res<AssetRegistry>,
+    loaded: Res<Assets<LoadedUntypedAsset>>,
+    tables: Res<Assets<StringTable>>,
+    mut loaded_events: EventReader<AssetEvent<LoadedUntypedAsset>>,
+    mut table_events: EventReader<AssetEvent<StringTable>>,
+    mut strings: ResMut<Strings>,
+) {
+    let loaded_changed = loaded_events.read().count() > 0;
+    let tables_changed = table_events.read().count() > 0;
+    let filter_changed = filter.is_changed() || rated_texts.is_changed();
+    if !loaded_changed && !tables_changed && !locale.is_changed() && !filter_changed {
+        return;
+    }
+    let table = |language: Language| {
+        registry
+            .data::<StringTable>(&language.table_key(), &loaded)
+            .and_then(|handle| tables.get(&handle))
+    };
+    let mut new_strings = Strings::new(table(locale.language), table(Language::FALLBACK));
+    for text in rated_texts.0.iter() {
+        if !filter.allows(text.rating) {
+            new_strings.censor(&text.key, text.censored.as_deref());
+        }
+    }
+    *strings = new_strings;
+}
+
+fn localize_texts(
+    strings: Res<Strings>,
+    mut text_query: Query<(Ref<Localized>, &mut FontStackText)>,
+) {
+    for (localized, mut text) in &mut text_query {
+        if strings.is_changed() || localized.is_changed() {
+            text.sections[0].value = strings.format(&localized);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::path::Path;
+
+    fn read_table(language: Language) -> StringTable {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets/locales")
+            .join(format!("{}.strings.ron", language.code()));
+        let text = std::fs::read_to_string(&path)
+            .unwrap_or_else(|error| panic!("could not read {}: {error}", path.display()));
+        ron::from_str(&text)
+            .unwrap_or_else(|error| panic!("could not parse {}: {error}", path.display()))
+    }
+
+    // Keys given to `tr` in the source files
+    fn keys_used_in(dir: &Path, keys: &mut BTreeSet<String>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                keys_used_in(&path, keys);
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            // Tests use keys of their own
+            let source = source.split("#[cfg(test)]").next().unwrap_or_default();
+            for (start, _) in source.match_indices("tr(\"") {
+                let is_call = !source[..start].ends_with(|c: char| c.is_alphanumeric() || c == '_');
+                let rest = &source[start + 4..];
+                if let (true, Some(end)) = (is_call, rest.find('"')) {
+                    keys.insert(String::from(&rest[..end]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_language_has_every_key() {
+        let tables: Vec<_> = Language::ALL
+            .iter()
+            .map(|language| (*language, read_table(*language)))
+            .collect();
+        let mut keys = BTreeSet::new();
+        keys_used_in(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut keys,
+        );
+        for (_, table) in tables.iter() {
+            keys.extend(table.0.keys().cloned());
+        }
+
+        let missing: Vec<String> = tables
+            .iter()
+            .flat_map(|(language, table)| {
+                keys.iter()
+                    .filter(|key| !table.0.contains_key(*key))
+                    .map(move |key| format!("{}: {key}", language.code()))
+            })
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "missing strings:\n{}",
+            missing.join("\n")
+        );
+    }
+
+    #[test]
+    fn missing_strings_fall_back_to_simplified_chinese() {
+        let table = StringTable(HashMap::from([(
+            String::from("greeting"),
+            String::from("Hello {name}"),
+        )]));
+        let fallback = StringTable(HashMap::from([
+            (String::from("greeting"), String::from("你好 {name}")),
+            (String::from("farewell"), String::from("再见")),
+        ]));
+        let strings = Strings::new(Some(&table), Some(&fallback));
+
+        assert_eq!(
+            strings.format(&tr("greeting").arg("name", "Ada")),
+            "Hello Ada"
+        );
+        assert_eq!(strings.get("farewell"), "再见");
+        assert_eq!(strings.get("unknown"), "unknown");
+    }
+}
@@ -2,21 +2,68 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 use bevy::asset::AssetMetaCheck;
 use bevy::prelude::*;
-use bevy::window::WindowMode;
 
-use roguelike_demo::GamePlugin;
+use roguelike_demo::{GamePlugin, HeadlessCapture, RequestedSeed, Settings};
+
+const USAGE: &str = "Usage: roguelike_demo [--seed <number>] [--capture <GameState> <file.png>]";
+
+fn exit_with_usage(error: Option<String>) -> ! {
+    match error {
+        Some(error) => eprintln!("{error}\n{USAGE}"),
+        None => eprintln!("{USAGE}"),
+    }
+    std::process::exit(2);
+}
 
 fn main() {
-    App::new()
-        .insert_resource(AssetMetaCheck::Never)
+    // `--seed` plays the first run with the seed, `--capture` renders a game
+    // state to a file and quits
+    let mut args = std::env::args().skip(1);
+    let (mut seed, mut capture) = (None, None);
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--seed" if seed.is_none() => {
+                let number = args.next().unwrap_or_else(|| exit_with_usage(None));
+                match number.parse() {
+                    Ok(number) => seed = Some(RequestedSeed(number)),
+                    Err(_) => exit_with_usage(Some(format!("{number} is not a seed"))),
+                }
+            }
+            "--capture" if capture.is_none() => {
+                let (Some(state), Some(path)) = (args.next(), args.next()) else {
+                    exit_with_usage(None);
+                };
+                match HeadlessCapture::new(&state, path) {
+                    Ok(found) => capture = Some(found),
+                    Err(error) => exit_with_usage(Some(error)),
+                }
+            }
+            _ => exit_with_usage(None),
+        }
+    }
+
+    // The window is created as the player last set it
+    let (settings, window) = match &capture {
+        Some(capture) => (capture.settings(), capture.window()),
+        None => {
+            let settings = Settings::load();
+            let window = settings.window();
+            (settings, window)
+        }
+    };
+    let mut app = App::new();
+    app.insert_resource(AssetMetaCheck::Never)
         .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                resizable: false,
-                mode: WindowMode::BorderlessFullscreen,
-                ..default()
-            }),
+            primary_window: Some(window),
             ..default()
         }))
-        .add_plugins(GamePlugin)
-        .run();
+        .insert_resource(settings)
+        .add_plugins(GamePlugin);
+    if let Some(capture) = capture {
+        app.insert_resource(capture);
+    }
+    if let Some(seed) = seed {
+        app.insert_resource(seed);
+    }
+    app.run();
 }
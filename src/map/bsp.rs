@@ -0,0 +1,272 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+
+use super::{Coord, Level, MapGenerator, TileKind, TileMap};
+
+/// Rooms joined by corridors. The map is split in two again and again,
+/// each leaf of the splits gets a room and the two halves of every split
+/// are joined by a corridor, so every room can be reached.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct BspGenerator {
+    pub width: u32,
+    pub height: u32,
+    /// Smallest width and height of a room, without its walls
+    pub min_room: u32,
+    /// Largest width and height of a room, without its walls
+    pub max_room: u32,
+}
+
+impl Default for BspGenerator {
+    fn default() -> Self {
+        Self {
+            width: 64,
+            height: 40,
+            min_room: 4,
+            max_room: 10,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Rect {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl Rect {
+    fn center(&self) -> Coord {
+        Coord::new(self.x + self.width / 2, self.y + self.height / 2)
+    }
+}
+
+impl MapGenerator for BspGenerator {
+    fn validate(&self) -> Result<(), String> {
+        if self.min_room < 1 || self.min_room > self.max_room {
+            return Err(String::from("rooms need a size from 1 to the largest size"));
+        }
+        if self.width < self.min_room + 2 || self.height < self.min_room + 2 {
+            return Err(String::from("the map must fit a room with its walls"));
+        }
+        Ok(())
+    }
+
+    fn generate(&self, seed: u64) -> Level {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut map = TileMap::new(self.width, self.height, TileKind::Wall);
+        let mut rooms = Vec::new();
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: self.width as i32,
+            height: self.height as i32,
+        };
+        self.split(&mut rng, &mut map, &mut rooms, area);
+
+        // The up stairs in a random room, the down stairs in the room
+        // farthest from them, or as far as they go in a single room
+        let entrance = rooms[rng.gen_range(0..rooms.len())].center();
+        let distances = map.walking_distances(entrance);
+        let distance = |coord: &Coord| distances.get(coord).copied().unwrap_or_default();
+        let mut exit = rooms
+            .iter()
+            .map(Rect::center)
+            .max_by_key(distance)
+            .unwrap_or(entrance);
+        if exit == entrance {
+            exit = distances
+                .iter()
+                .max_by_key(|(coord, distance)| (**distance, **coord))
+                .map_or(entrance, |(coord, _)| *coord);
+        }
+        map.set_kind(entrance, TileKind::StairsUp);
+        map.set_kind(exit, TileKind::StairsDown);
+        Level::new(map, entrance)
+    }
+}
+
+impl BspGenerator {
+    // Carve rooms into `area` and join them, returns the rooms carved
+    fn split(
+        &self,
+        rng: &mut ChaCha8Rng,
+        map: &mut TileMap,
+        rooms: &mut Vec<Rect>,
+        area: Rect,
+    ) -> std::ops::Range<usize> {
+        // A leaf fits the largest room with the walls around it, smaller
+        // areas are kept as they are
+        let min_leaf = self.min_room as i32 + 2;
+        let max_leaf = self.max_room as i32 + 2;
+        let can_split_x = area.width >= 2 * min_leaf;
+        let can_split_y = area.height >= 2 * min_leaf;
+        let fits = area.width <= max_leaf && area.height <= max_leaf;
+        if !(can_split_x || can_split_y) || (fits && rng.gen_bool(0.5)) {
+            let start = rooms.len();
+            rooms.push(self.carve_room(rng, map, area));
+            return start..rooms.len();
+        }
+
+        // Split across the longer side when both can be split
+        let split_x = match (can_split_x, can_split_y) {
+            (true, true) if area.width != area.height => area.width > area.height,
+            (true, true) => rng.gen_bool(0.5),
+            (split_x, _) => split_x,
+        };
+        let (first, second) = if split_x {
+            let at = rng.gen_range(min_leaf..=area.width - min_leaf);
+            (
+                Rect { width: at, ..area },
+                Rect {
+                    x: area.x + at,
+                    width: area.width - at,
+                    ..area
+                },
+            )
+        } else {
+            let at = rng.gen_range(min_leaf..=area.height - min_leaf);
+            (
+                Rect { height: at, ..area },
+                Rect {
+                    y: area.y + at,
+                    height: area.height - at,
+                    ..area
+                },
+            )
+        };
+        let first = self.split(rng, map, rooms, first);
+        let second = self.split(rng, map, rooms, second);
+
+        // Join a room of each half, each half is joined within already
+        let from = rooms[rng.gen_range(first.clone())].center();
+        let to = rooms[rng.gen_range(second.clone())].center();
+        carve_corridor(rng, map, from, to);
+        first.start..second.end
+    }
+
+    // A room in `area`, walls all around it inside the area
+    fn carve_room(&self, rng: &mut ChaCha8Rng, map: &mut TileMap, area: Rect) -> Rect {
+        let mut size = |space: i32| {
+            let max = (self.max_room as i32).min(space - 2);
+            rng.gen_range((self.min_room as i32).min(max)..=max)
+        };
+        let (width, height) = (size(area.width), size(area.height));
+        let room = Rect {
+            x: area.x + rng.gen_range(1..=area.width - width - 1),
+            y: area.y + rng.gen_range(1..=area.height - height - 1),
+            width,
+            height,
+        };
+        for y in room.y..room.y + room.height {
+            for x in room.x..room.x + room.width {
+                map.set_kind(Coord::new(x, y), TileKind::Floor);
+            }
+        }
+        room
+    }
+}
+
+// An L shaped corridor, horizontal or vertical first
+fn carve_corridor(rng: &mut ChaCha8Rng, map: &mut TileMap, from: Coord, to: Coord) {
+    let corner = if rng.gen_bool(0.5) {
+        Coord::new(to.x, from.y)
+    } else {
+        Coord::new(from.x, to.y)
+    };
+    for (start, end) in [(from, corner), (corner, to)] {
+        for y in start.y.min(end.y)..=start.y.max(end.y) {
+            for x in start.x.min(end.x)..=start.x.max(end.x) {
+                map.set_kind(Coord::new(x, y), TileKind::Floor);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::generator::tests::assert_playable;
+    use super::*;
+
+    const SEEDS: u64 = 3000;
+
+    #[test]
+    fn every_floor_tile_is_reachable_from_the_entrance() {
+        let generator = BspGenerator::default();
+        for seed in 0..SEEDS {
+            assert_playable(&generator.generate(seed), seed);
+        }
+    }
+
+    #[test]
+    fn other_sizes_are_connected_too() {
+        let generators = [
+            BspGenerator {
+                width: 20,
+                height: 12,
+                min_room: 3,
+                max_room: 5,
+            },
+            BspGenerator {
+                width: 120,
+                height: 30,
+                min_room: 2,
+                max_room: 16,
+            },
+            // Room for a single room only
+            BspGenerator {
+                width: 8,
+                height: 8,
+                min_room: 6,
+                max_room: 6,
+            },
+        ];
+        for generator in generators {
+            for seed in 0..SEEDS / 3 {
+                assert_playable(&generator.generate(seed), seed);
+            }
+        }
+    }
+
+    #[test]
+    fn the_map_is_walled_in() {
+        for seed in 0..SEEDS / 10 {
+            let level = BspGenerator::default().generate(seed);
+            let map = &level.map;
+            let (width, height) = (map.width() as i32, map.height() as i32);
+            for (coord, tile) in map.iter() {
+                let edge =
+                    coord.x == 0 || coord.y == 0 || coord.x == width - 1 || coord.y == height - 1;
+                if edge {
+                    assert_eq!(tile.kind(), TileKind::Wall, "{coord:?} with seed {seed}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn the_stairs_are_far_apart() {
+        let generator = BspGenerator::default();
+        for seed in 0..SEEDS / 10 {
+            let level = generator.generate(seed);
+            let distances = level.map.walking_distances(level.entrance);
+            let exit = distances
+                .iter()
+                .find(|(coord, _)| level.map.kind(**coord) == TileKind::StairsDown)
+                .map(|(_, distance)| *distance)
+                .unwrap();
+            // At least half as far as the farthest tile
+            let farthest = distances.values().max().unwrap();
+            assert!(2 * exit >= *farthest, "stairs too close with seed {seed}");
+        }
+    }
+
+    #[test]
+    fn a_seed_always_gives_the_same_level() {
+        let generator = BspGenerator::default();
+        assert_eq!(generator.generate(7).map, generator.generate(7).map);
+        assert_ne!(generator.generate(7).map, generator.generate(8).map);
+    }
+}
@@ -0,0 +1,175 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use super::generator::place_stairs;
+use super::{Coord, Level, MapGenerator, TileKind, TileMap};
+
+// Caves smaller than this share of the map are generated again
+const MIN_OPEN: f32 = 0.25;
+
+// Tries at a large enough cave before settling for a small one
+const ATTEMPTS: u32 = 10;
+
+/// Caves grown by a cellular automaton. The map starts as random noise and
+/// every step a tile becomes wall when most tiles around it are walls, and
+/// floor when few are. Pockets not joined to the largest cave are filled.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CaveGenerator {
+    pub width: u32,
+    pub height: u32,
+    /// Share of the tiles that start as wall
+    pub fill: f32,
+    /// Steps of the automaton
+    pub steps: u32,
+}
+
+impl Default for CaveGenerator {
+    fn default() -> Self {
+        Self {
+            width: 64,
+            height: 40,
+            fill: 0.45,
+            steps: 5,
+        }
+    }
+}
+
+impl MapGenerator for CaveGenerator {
+    fn validate(&self) -> Result<(), String> {
+        if self.width < 4 || self.height < 3 {
+            return Err(String::from(
+                "the map must fit two floor tiles inside its walls",
+            ));
+        }
+        Ok(())
+    }
+
+    fn generate(&self, seed: u64) -> Level {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let interior = ((self.width - 2) * (self.height - 2)) as f32;
+        let mut map = self.grow_cave(&mut rng);
+        for _ in 1..ATTEMPTS {
+            if floor_count(&map) as f32 >= MIN_OPEN * interior {
+                break;
+            }
+            map = self.grow_cave(&mut rng);
+        }
+        // Never leave a level without room for both stairs
+        if floor_count(&map) < 2 {
+            map = TileMap::new(self.width, self.height, TileKind::Wall);
+            let center = Coord::new(self.width as i32 / 2, self.height as i32 / 2);
+            map.set_kind(center, TileKind::Floor);
+            map.set_kind(center + Coord::WEST, TileKind::Floor);
+        }
+        let entrance = place_stairs(&mut map, &mut rng);
+        Level::new(map, entrance)
+    }
+}
+
+impl CaveGenerator {
+    // The largest cave of one run of the automaton
+    fn grow_cave(&self, rng: &mut ChaCha8Rng) -> TileMap {
+        let mut map = TileMap::new(self.width, self.height, TileKind::Wall);
+        for coord in interior(&map) {
+            if !rng.gen_bool(self.fill.clamp(0.0, 1.0) as f64) {
+                map.set_kind(coord, TileKind::Floor);
+            }
+        }
+        for _ in 0..self.steps {
+            let mut next = map.clone();
+            for coord in interior(&map) {
+                // The map's edge counts as wall
+                let walls = coord
+                    .neighbours()
+                    .filter(|neighbour| map.kind(*neighbour) == TileKind::Wall)
+                    .count();
+                if walls >= 5 {
+                    next.set_kind(coord, TileKind::Wall);
+                } else if walls <= 3 {
+                    next.set_kind(coord, TileKind::Floor);
+                }
+            }
+            map = next;
+        }
+        keep_largest_cave(&mut map);
+        map
+    }
+}
+
+// Every tile but the walls around the map
+fn interior(map: &TileMap) -> Vec<Coord> {
+    let (width, height) = (map.width() as i32, map.height() as i32);
+    map.coords()
+        .filter(|coord| coord.x > 0 && coord.y > 0 && coord.x < width - 1 && coord.y < height - 1)
+        .collect()
+}
+
+fn floor_count(map: &TileMap) -> usize {
+    map.iter()
+        .filter(|(_, tile)| tile.kind() == TileKind::Floor)
+        .count()
+}
+
+/// Fill every floor tile that cannot be walked to from the largest cave
+fn keep_largest_cave(map: &mut TileMap) {
+    let mut caves: Vec<Vec<Coord>> = Vec::new();
+    let mut known = HashSet::new();
+    for (coord, tile) in map.iter() {
+        if tile.kind() == TileKind::Floor && !known.contains(&coord) {
+            let cave: Vec<Coord> = map.walking_distances(coord).into_keys().collect();
+            known.extend(cave.iter().copied());
+            caves.push(cave);
+        }
+    }
+    // The first of the largest caves, counted row by row
+    let largest = caves
+        .iter()
+        .enumerate()
+        .max_by_key(|(index, cave)| (cave.len(), Reverse(*index)))
+        .map(|(index, _)| index);
+    for (index, cave) in caves.iter().enumerate() {
+        if Some(index) != largest {
+            for coord in cave {
+                map.set_kind(*coord, TileKind::Wall);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::generator::tests::assert_playable;
+    use super::*;
+
+    #[test]
+    fn every_cave_tile_is_reachable_from_the_entrance() {
+        let generator = CaveGenerator::default();
+        for seed in 0..1000 {
+            assert_playable(&generator.generate(seed), seed);
+        }
+    }
+
+    #[test]
+    fn crowded_and_tiny_maps_still_get_both_stairs() {
+        let generators = [
+            CaveGenerator {
+                fill: 0.9,
+                ..CaveGenerator::default()
+            },
+            CaveGenerator {
+                width: 4,
+                height: 3,
+                ..CaveGenerator::default()
+            },
+        ];
+        for generator in generators {
+            for seed in 0..100 {
+                assert_playable(&generator.generate(seed), seed);
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+
+use super::generator::place_stairs;
+use super::{Coord, Level, MapGenerator, TileKind, TileMap};
+
+/// Winding tunnels dug by walkers stumbling about at random. Every walker
+/// starts on a tile already dug, so the tunnels are all joined.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DrunkardGenerator {
+    pub width: u32,
+    pub height: u32,
+    /// Share of the tiles inside the map's walls dug out before stopping
+    pub floor: f32,
+    /// Steps of a walker before the next one starts
+    pub steps: u32,
+}
+
+impl Default for DrunkardGenerator {
+    fn default() -> Self {
+        Self {
+            width: 64,
+            height: 40,
+            floor: 0.4,
+            steps: 200,
+        }
+    }
+}
+
+impl MapGenerator for DrunkardGenerator {
+    fn validate(&self) -> Result<(), String> {
+        if self.width < 4 || self.height < 3 {
+            return Err(String::from(
+                "the map must fit two floor tiles inside its walls",
+            ));
+        }
+        Ok(())
+    }
+
+    fn generate(&self, seed: u64) -> Level {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut map = TileMap::new(self.width, self.height, TileKind::Wall);
+        let (max_x, max_y) = (self.width as i32 - 2, self.height as i32 - 2);
+        let interior = (max_x * max_y) as usize;
+        let target = ((self.floor.clamp(0.0, 1.0) * interior as f32) as usize).clamp(2, interior);
+
+        let start = Coord::new(self.width as i32 / 2, self.height as i32 / 2);
+        map.set_kind(start, TileKind::Floor);
+        let mut dug = vec![start];
+        while dug.len() < target {
+            let mut walker = dug[rng.gen_range(0..dug.len())];
+            for _ in 0..self.steps.max(1) {
+                let step = Coord::CARDINALS[rng.gen_range(0..Coord::CARDINALS.len())];
+                let next = walker + step;
+                // The walls around the map are never dug
+                if next.x < 1 || next.y < 1 || next.x > max_x || next.y > max_y {
+                    continue;
+                }
+                walker = next;
+                if map.kind(walker) == TileKind::Wall {
+                    map.set_kind(walker, TileKind::Floor);
+                    dug.push(walker);
+                    if dug.len() >= target {
+                        break;
+                    }
+                }
+            }
+        }
+        let entrance = place_stairs(&mut map, &mut rng);
+        Level::new(map, entrance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::generator::tests::assert_playable;
+    use super::*;
+
+    #[test]
+    fn every_tunnel_tile_is_reachable_from_the_entrance() {
+        let generator = DrunkardGenerator::default();
+        for seed in 0..1000 {
+            assert_playable(&generator.generate(seed), seed);
+        }
+    }
+
+    #[test]
+    fn the_tunnels_dig_out_the_given_share() {
+        let generator = DrunkardGenerator {
+            width: 12,
+            height: 7,
+            floor: 1.0,
+            steps: 1,
+        };
+        let level = generator.generate(3);
+        assert_playable(&level, 3);
+        let walls = level
+            .map
+            .iter()
+            .filter(|(_, tile)| tile.kind() == TileKind::Wall)
+            .count();
+        // Only the walls around the map are left
+        assert_eq!(walls, 12 * 7 - 10 * 5);
+    }
+}
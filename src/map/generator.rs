@@ -0,0 +1,227 @@
+use bevy::asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{BspGenerator, CaveGenerator, Coord, DrunkardGenerator, Spawn, TileKind, TileMap};
+
+/// A generated dungeon level, the player starts on the `entrance`, the up
+/// stairs. `spawns` are the monsters and items placed by its prefabs.
+pub struct Level {
+    pub map: TileMap,
+    pub entrance: Coord,
+    pub spawns: Vec<(Coord, Spawn)>,
+}
+
+impl Level {
+    pub fn new(map: TileMap, entrance: Coord) -> Self {
+        Self {
+            map,
+            entrance,
+            spawns: Vec::new(),
+        }
+    }
+}
+
+/// Turns a seed into a level, the same seed always gives the same level.
+/// Every floor tile of the level can be walked to from the entrance.
+pub trait MapGenerator {
+    /// Why the settings cannot generate a level, checked when the level
+    /// config is loaded
+    fn validate(&self) -> Result<(), String>;
+
+    /// Generate a level, the settings must be valid
+    fn generate(&self, seed: u64) -> Level;
+}
+
+/// A generator with its settings, as named in a `.levels.ron` file
+#[derive(Deserialize, Clone, Debug)]
+pub enum GeneratorConfig {
+    Bsp(BspGenerator),
+    Caves(CaveGenerator),
+    DrunkardsWalk(DrunkardGenerator),
+}
+
+impl GeneratorConfig {
+    pub fn generator(&self) -> &dyn MapGenerator {
+        match self {
+            GeneratorConfig::Bsp(generator) => generator,
+            GeneratorConfig::Caves(generator) => generator,
+            GeneratorConfig::DrunkardsWalk(generator) => generator,
+        }
+    }
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig::Bsp(BspGenerator::default())
+    }
+}
+
+/// Generator of the levels from `from_depth` down, and how many prefabs to
+/// try to stamp into each
+#[derive(Deserialize, Clone, Debug)]
+pub struct DepthConfig {
+    pub from_depth: u32,
+    pub generator: GeneratorConfig,
+    #[serde(default)]
+    pub prefabs: u32,
+}
+
+/// Generators of the dungeon depths, read from a `.levels.ron` file
+#[derive(Asset, TypePath, Deserialize, Clone, Debug, Default)]
+pub struct LevelConfig {
+    pub depths: Vec<DepthConfig>,
+}
+
+impl LevelConfig {
+    /// The deepest entry starting at or above `depth`, `None` when every
+    /// entry starts deeper
+    pub fn depth(&self, depth: u32) -> Option<&DepthConfig> {
+        self.depths
+            .iter()
+            .filter(|config| config.from_depth <= depth)
+            .max_by_key(|config| config.from_depth)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LevelConfigLoaderError {
+    #[error("could not read level config: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse level config: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid generator from depth {depth}: {reason}")]
+    Invalid { depth: u32, reason: String },
+}
+
+impl LevelConfig {
+    // Read a `.levels.ron` file, every generator in it must be valid
+    fn parse(bytes: &[u8]) -> Result<Self, LevelConfigLoaderError> {
+        let config = ron::de::from_bytes::<LevelConfig>(bytes)?;
+        for depth in config.depths.iter() {
+            depth.generator.generator().validate().map_err(|reason| {
+                LevelConfigLoaderError::Invalid {
+                    depth: depth.from_depth,
+                    reason,
+                }
+            })?;
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Default)]
+pub struct LevelConfigLoader;
+
+impl AssetLoader for LevelConfigLoader {
+    type Asset = LevelConfig;
+    type Settings = ();
+    type Error = LevelConfigLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            LevelConfig::parse(&bytes)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["levels.ron"]
+    }
+}
+
+/// Put the up and down stairs on floor tiles about as far apart as the
+/// level allows, returns the up stairs. The level needs two floor tiles.
+pub fn place_stairs(map: &mut TileMap, rng: &mut ChaCha8Rng) -> Coord {
+    let floor: Vec<Coord> = map
+        .iter()
+        .filter(|(_, tile)| tile.kind() == TileKind::Floor)
+        .map(|(coord, _)| coord)
+        .collect();
+    let start = floor[rng.gen_range(0..floor.len())];
+    // The tile farthest from any tile is near one end of the longest walk,
+    // the tile farthest from that is near the other end
+    let farthest = |map: &TileMap, from: Coord| {
+        map.walking_distances(from)
+            .into_iter()
+            .max_by_key(|(coord, distance)| (*distance, *coord))
+            .map_or(from, |(coord, _)| coord)
+    };
+    let exit = farthest(map, start);
+    let entrance = farthest(map, exit);
+    map.set_kind(entrance, TileKind::StairsUp);
+    map.set_kind(exit, TileKind::StairsDown);
+    entrance
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Every passable tile can be walked to from the entrance, and there is
+    /// one up and one down stairs
+    pub fn assert_playable(level: &Level, seed: u64) {
+        let distances = level.map.walking_distances(level.entrance);
+        let mut stairs = (0, 0);
+        for (coord, tile) in level.map.iter() {
+            if tile.kind().is_passable() {
+                assert!(
+                    distances.contains_key(&coord),
+                    "{coord:?} cannot be reached from the entrance with seed {seed}"
+                );
+            }
+            match tile.kind() {
+                TileKind::StairsUp => stairs.0 += 1,
+                TileKind::StairsDown => stairs.1 += 1,
+                _ => {}
+            }
+        }
+        assert_eq!(stairs, (1, 1), "stairs of seed {seed}");
+        assert_eq!(level.map.kind(level.entrance), TileKind::StairsUp);
+    }
+
+    #[test]
+    fn every_depth_of_the_level_config_has_a_generator() {
+        let path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/data/dungeon.levels.ron");
+        let text = std::fs::read_to_string(path).unwrap();
+        let config = LevelConfig::parse(text.as_bytes()).unwrap();
+        for depth in 1..=100 {
+            let config = config.depth(depth).expect("a generator for every depth");
+            assert_playable(
+                &config.generator.generator().generate(depth.into()),
+                depth.into(),
+            );
+        }
+    }
+
+    #[test]
+    fn generators_that_cannot_generate_fail_loading() {
+        let invalid = [
+            "Bsp(min_room: 8, max_room: 6)",
+            "Bsp(width: 5, min_room: 4)",
+            "Caves(width: 3)",
+            "DrunkardsWalk(height: 2)",
+        ];
+        for generator in invalid {
+            let text = format!(
+                "#![enable(unwrap_variant_newtypes)]\n\
+                 (depths: [(from_depth: 1, generator: Bsp()), (from_depth: 4, generator: {generator})])"
+            );
+            match LevelConfig::parse(text.as_bytes()) {
+                Err(LevelConfigLoaderError::Invalid { depth, .. }) => assert_eq!(depth, 4),
+                other => panic!("{generator} loaded as {other:?}"),
+            }
+        }
+    }
+}
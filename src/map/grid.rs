@@ -0,0 +1,329 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::ops::{Add, Sub};
+
+/// Position of a tile, `x` grows east and `y` grows south
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Coord {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Coord {
+    pub const NORTH: Coord = Coord::new(0, -1);
+    pub const EAST: Coord = Coord::new(1, 0);
+    pub const SOUTH: Coord = Coord::new(0, 1);
+    pub const WEST: Coord = Coord::new(-1, 0);
+
+    /// Steps to the four orthogonal neighbours, clockwise from north
+    pub const CARDINALS: [Coord; 4] = [Coord::NORTH, Coord::EAST, Coord::SOUTH, Coord::WEST];
+
+    /// Steps to all eight neighbours, clockwise from north
+    pub const DIRECTIONS: [Coord; 8] = [
+        Coord::NORTH,
+        Coord::new(1, -1),
+        Coord::EAST,
+        Coord::new(1, 1),
+        Coord::SOUTH,
+        Coord::new(-1, 1),
+        Coord::WEST,
+        Coord::new(-1, -1),
+    ];
+
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    /// The eight tiles around this one, clockwise from north
+    pub fn neighbours(self) -> impl Iterator<Item = Coord> {
+        Coord::DIRECTIONS.into_iter().map(move |step| self + step)
+    }
+
+    /// The four tiles sharing an edge with this one, clockwise from north
+    pub fn cardinal_neighbours(self) -> impl Iterator<Item = Coord> {
+        Coord::CARDINALS.into_iter().map(move |step| self + step)
+    }
+
+    /// Moves needed to get to `other` when diagonal moves count as one
+    // Not used by gameplay until there are monsters to chase the player
+    #[allow(dead_code)]
+    pub fn distance(self, other: Coord) -> u32 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// Moves needed to get to `other` without diagonal moves
+    #[allow(dead_code)]
+    pub fn manhattan_distance(self, other: Coord) -> u32 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl Add for Coord {
+    type Output = Coord;
+
+    fn add(self, other: Coord) -> Coord {
+        Coord::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Coord {
+    type Output = Coord;
+
+    fn sub(self, other: Coord) -> Coord {
+        Coord::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum TileKind {
+    #[default]
+    Wall,
+    Floor,
+    ClosedDoor,
+    OpenDoor,
+    StairsUp,
+    StairsDown,
+    Water,
+}
+
+impl TileKind {
+    pub fn blocks_movement(self) -> bool {
+        matches!(self, TileKind::Wall | TileKind::Water)
+    }
+
+    pub fn blocks_sight(self) -> bool {
+        matches!(self, TileKind::Wall | TileKind::ClosedDoor)
+    }
+
+    /// Walked on, or opened by walking into it
+    pub fn is_passable(self) -> bool {
+        !self.blocks_movement()
+    }
+}
+
+/// Set of `TileFlags::*` flags
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TileFlags(u8);
+
+impl TileFlags {
+    pub const BLOCKS_MOVEMENT: TileFlags = TileFlags(1);
+    pub const BLOCKS_SIGHT: TileFlags = TileFlags(1 << 1);
+    /// Seen by the player at some point, drawn from memory when not visible
+    pub const REVEALED: TileFlags = TileFlags(1 << 2);
+    /// In the player's field of view
+    pub const VISIBLE: TileFlags = TileFlags(1 << 3);
+
+    pub fn contains(self, flags: TileFlags) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+
+    pub fn set(&mut self, flags: TileFlags, on: bool) {
+        if on {
+            self.0 |= flags.0;
+        } else {
+            self.0 &= !flags.0;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tile {
+    kind: TileKind,
+    flags: TileFlags,
+}
+
+impl Tile {
+    pub fn new(kind: TileKind) -> Self {
+        let mut tile = Self::default();
+        tile.set_kind(kind);
+        tile
+    }
+
+    pub fn kind(&self) -> TileKind {
+        self.kind
+    }
+
+    /// Change what the tile is, the movement and sight flags follow it
+    pub fn set_kind(&mut self, kind: TileKind) {
+        self.kind = kind;
+        self.flags
+            .set(TileFlags::BLOCKS_MOVEMENT, kind.blocks_movement());
+        self.flags.set(TileFlags::BLOCKS_SIGHT, kind.blocks_sight());
+    }
+
+    // Read by saves once the map is saved
+    #[allow(dead_code)]
+    pub fn flags(&self) -> TileFlags {
+        self.flags
+    }
+
+    pub fn contains(&self, flags: TileFlags) -> bool {
+        self.flags.contains(flags)
+    }
+
+    /// Set whether the tile is revealed or visible, the other flags follow
+    /// its kind
+    pub fn set_seen(&mut self, flags: TileFlags, on: bool) {
+        debug_assert!(
+            !flags.contains(TileFlags::BLOCKS_MOVEMENT) && !flags.contains(TileFlags::BLOCKS_SIGHT),
+            "movement and sight flags follow the tile kind"
+        );
+        self.flags.set(flags, on);
+    }
+}
+
+/// Tiles of the current dungeon level, row by row from the north west corner
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct TileMap {
+    width: u32,
+    height: u32,
+    tiles: Vec<Tile>,
+}
+
+impl TileMap {
+    /// A map of `width` by `height` tiles of `kind`
+    pub fn new(width: u32, height: u32, kind: TileKind) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![Tile::new(kind); (width * height) as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn in_bounds(&self, coord: Coord) -> bool {
+        (0..self.width as i32).contains(&coord.x) && (0..self.height as i32).contains(&coord.y)
+    }
+
+    fn index(&self, coord: Coord) -> Option<usize> {
+        self.in_bounds(coord)
+            .then(|| (coord.y as u32 * self.width + coord.x as u32) as usize)
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&Tile> {
+        self.index(coord).map(|index| &self.tiles[index])
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut Tile> {
+        self.index(coord).map(|index| &mut self.tiles[index])
+    }
+
+    /// Kind of the tile at `coord`, walls all around the map
+    pub fn kind(&self, coord: Coord) -> TileKind {
+        self.get(coord).map_or(TileKind::Wall, Tile::kind)
+    }
+
+    /// Set the kind of the tile at `coord`, nothing happens out of bounds
+    pub fn set_kind(&mut self, coord: Coord, kind: TileKind) {
+        if let Some(tile) = self.get_mut(coord) {
+            tile.set_kind(kind);
+        }
+    }
+
+    /// Nothing can move out of the map
+    pub fn blocks_movement(&self, coord: Coord) -> bool {
+        self.get(coord)
+            .is_none_or(|tile| tile.contains(TileFlags::BLOCKS_MOVEMENT))
+    }
+
+    // Read by the field of view once there is one
+    #[allow(dead_code)]
+    pub fn blocks_sight(&self, coord: Coord) -> bool {
+        self.get(coord)
+            .is_none_or(|tile| tile.contains(TileFlags::BLOCKS_SIGHT))
+    }
+
+    /// Every coordinate of the map, row by row
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let width = self.width as i32;
+        (0..self.height as i32).flat_map(move |y| (0..width).map(move |x| Coord::new(x, y)))
+    }
+
+    /// Every tile of the map with its coordinate, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &Tile)> {
+        self.coords().zip(self.tiles.iter())
+    }
+
+    /// The neighbours of `coord` inside the map, clockwise from north
+    // Not used by gameplay until there is movement
+    #[allow(dead_code)]
+    pub fn neighbours(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        coord
+            .neighbours()
+            .filter(|neighbour| self.in_bounds(*neighbour))
+    }
+
+    /// The cardinal neighbours of `coord` inside the map, clockwise from
+    /// north
+    pub fn cardinal_neighbours(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        coord
+            .cardinal_neighbours()
+            .filter(|neighbour| self.in_bounds(*neighbour))
+    }
+
+    /// Steps to walk from `start` to every tile that can be walked to,
+    /// without diagonal moves
+    pub fn walking_distances(&self, start: Coord) -> HashMap<Coord, u32> {
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(coord) = queue.pop_front() {
+            let distance = distances[&coord];
+            for neighbour in self.cardinal_neighbours(coord) {
+                if !self.blocks_movement(neighbour) && !distances.contains_key(&neighbour) {
+                    distances.insert(neighbour, distance + 1);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Set the seen flags of every tile at once
+    pub fn set_all_seen(&mut self, flags: TileFlags, on: bool) {
+        for tile in self.tiles.iter_mut() {
+            tile.set_seen(flags, on);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbours_stay_inside_the_map() {
+        let map = TileMap::new(3, 2, TileKind::Floor);
+        assert_eq!(map.neighbours(Coord::new(1, 0)).count(), 5);
+        assert_eq!(map.neighbours(Coord::new(0, 1)).count(), 3);
+        assert_eq!(
+            map.cardinal_neighbours(Coord::new(0, 0))
+                .collect::<Vec<_>>(),
+            [Coord::new(1, 0), Coord::new(0, 1)]
+        );
+        assert!(map.blocks_movement(Coord::new(3, 0)));
+        assert_eq!(map.iter().count(), 6);
+    }
+
+    #[test]
+    fn tile_flags_follow_the_kind_and_keep_what_was_seen() {
+        let mut tile = Tile::new(TileKind::ClosedDoor);
+        tile.set_seen(TileFlags::REVEALED, true);
+        assert!(tile.contains(TileFlags::BLOCKS_SIGHT));
+        assert!(!tile.contains(TileFlags::BLOCKS_MOVEMENT));
+
+        tile.set_kind(TileKind::Wall);
+        assert!(tile.contains(TileFlags::BLOCKS_MOVEMENT));
+        assert!(tile.contains(TileFlags::REVEALED));
+        tile.set_kind(TileKind::Water);
+        assert!(!tile.contains(TileFlags::BLOCKS_SIGHT));
+    }
+}
@@ -0,0 +1,183 @@
+use bevy::asset::LoadedUntypedAsset;
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::assetloader::AssetRegistry;
+use crate::camera::GameCamera;
+use crate::gamestate::{DespawnOnExit, GameState};
+use crate::rng::GameRng;
+use crate::run::RunStats;
+
+mod bsp;
+mod cave;
+mod drunkard;
+mod generator;
+mod grid;
+mod prefab;
+
+pub use bsp::BspGenerator;
+pub use cave::CaveGenerator;
+pub use drunkard::DrunkardGenerator;
+pub use generator::{GeneratorConfig, Level, LevelConfig, LevelConfigLoader, MapGenerator};
+pub use grid::{Coord, TileFlags, TileKind, TileMap};
+pub use prefab::{PrefabLibrary, PrefabLibraryLoader, Spawn};
+
+// Registry keys of the level config and the prefabs, loaded with the
+// dungeon asset set
+const LEVELS_KEY: &str = "levels";
+const PREFABS_KEY: &str = "prefabs";
+
+/// Size in pixels of a tile sprite
+pub const TILE_SIZE: f32 = 16.0;
+
+/// Creates the `TileMap` of the level on entering `GameState::GameRunning`
+/// and draws its tiles, both are removed again on exit
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LevelConfig>()
+            .init_asset_loader::<LevelConfigLoader>()
+            .init_asset::<PrefabLibrary>()
+            .init_asset_loader::<PrefabLibraryLoader>()
+            .add_systems(
+                OnEnter(GameState::GameRunning),
+                (create_map, spawn_tile_sprites).chain(),
+            )
+            .add_systems(OnExit(GameState::GameRunning), remove_map)
+            .add_systems(
+                Update,
+                draw_tiles
+                    .run_if(resource_exists_and_changed::<TileMap>)
+                    .run_if(in_state(GameState::GameRunning)),
+            );
+    }
+}
+
+/// Monsters and items the prefabs of the level place
+// Spawned by gameplay once there are monsters and items
+#[allow(dead_code)]
+#[derive(Resource, Default)]
+pub struct LevelSpawns(pub Vec<(Coord, Spawn)>);
+
+/// Sprite of the tile at the coordinate
+#[derive(Component)]
+pub struct MapTile(pub Coord);
+
+impl TileMap {
+    /// Centre of the tile sprite at `coord`, the map is centred on the origin
+    pub fn world_position(&self, coord: Coord) -> Vec2 {
+        let offset = Vec2::new(self.width() as f32, self.height() as f32) / 2.0 - 0.5;
+        // The map's y grows south
+        Vec2::new(coord.x as f32 - offset.x, offset.y - coord.y as f32) * TILE_SIZE
+    }
+}
+
+fn tile_color(kind: TileKind) -> Color {
+    match kind {
+        TileKind::Wall => Color::rgb(0.35, 0.33, 0.3),
+        TileKind::Floor => Color::rgb(0.75, 0.72, 0.65),
+        TileKind::ClosedDoor => Color::rgb(0.55, 0.35, 0.15),
+        TileKind::OpenDoor => Color::rgb(0.7, 0.5, 0.3),
+        TileKind::StairsUp => Color::rgb(0.9, 0.85, 0.4),
+        TileKind::StairsDown => Color::rgb(0.6, 0.55, 0.2),
+        TileKind::Water => Color::rgb(0.25, 0.45, 0.8),
+    }
+}
+
+/// Generate the level of the run's depth with the generator the level
+/// config gives the depth, stamp its prefabs and look at its entrance
+#[allow(clippy::too_many_arguments)]
+fn create_map(
+    mut commands: Commands,
+    run_stats: Res<RunStats>,
+    game_rng: Res<GameRng>,
+    registry: Res<AssetRegistry>,
+    loaded: Res<Assets<LoadedUntypedAsset>>,
+    configs: Res<Assets<LevelConfig>>,
+    libraries: Res<Assets<PrefabLibrary>>,
+    mut camera_query: Query<&mut Transform, With<GameCamera>>,
+) {
+    let config = registry
+        .data::<LevelConfig>(LEVELS_KEY, &loaded)
+        .and_then(|handle| configs.get(&handle))
+        .and_then(|config| config.depth(run_stats.depth));
+    let (generator, prefabs) = match config {
+        Some(config) => (config.generator.clone(), config.prefabs),
+        None => {
+            warn!(
+                "No generator for depth {} in the level config, using the default one",
+                run_stats.depth
+            );
+            (GeneratorConfig::default(), 0)
+        }
+    };
+    let mut rng = game_rng.level(run_stats.depth);
+    let mut level = generator.generator().generate(rng.gen());
+    let library = registry
+        .data::<PrefabLibrary>(PREFABS_KEY, &loaded)
+        .and_then(|handle| libraries.get(&handle));
+    if let Some(library) = library {
+        prefab::stamp_prefabs(&mut level, library, run_stats.depth, prefabs, &mut rng);
+    }
+    let Level {
+        mut map,
+        entrance,
+        spawns,
+    } = level;
+    // Until there is a field of view
+    map.set_all_seen(TileFlags::REVEALED, true);
+    map.set_all_seen(TileFlags::VISIBLE, true);
+    if let Ok(mut transform) = camera_query.get_single_mut() {
+        transform.translation = map.world_position(entrance).extend(transform.translation.z);
+    }
+    commands.insert_resource(map);
+    commands.insert_resource(LevelSpawns(spawns));
+}
+
+fn spawn_tile_sprites(mut commands: Commands, map: Res<TileMap>) {
+    for (coord, _) in map.iter() {
+        commands.spawn((
+            MapTile(coord),
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(TILE_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(map.world_position(coord).extend(0.0)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            DespawnOnExit(GameState::GameRunning),
+        ));
+    }
+}
+
+/// Colour the tile sprites by their kind, tiles out of sight are drawn dark
+/// from memory and unrevealed ones not at all
+fn draw_tiles(map: Res<TileMap>, mut tile_query: Query<(&MapTile, &mut Sprite, &mut Visibility)>) {
+    for (MapTile(coord), mut sprite, mut visibility) in tile_query.iter_mut() {
+        let Some(tile) = map.get(*coord) else {
+            continue;
+        };
+        let color = tile_color(tile.kind());
+        let (color, shown) = if tile.contains(TileFlags::VISIBLE) {
+            (color, Visibility::Inherited)
+        } else if tile.contains(TileFlags::REVEALED) {
+            (color * 0.5, Visibility::Inherited)
+        } else {
+            (color, Visibility::Hidden)
+        };
+        if sprite.color != color {
+            sprite.color = color;
+        }
+        if *visibility != shown {
+            *visibility = shown;
+        }
+    }
+}
+
+fn remove_map(mut commands: Commands) {
+    commands.remove_resource::<TileMap>();
+    commands.remove_resource::<LevelSpawns>();
+}
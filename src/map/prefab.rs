@@ -0,0 +1,391 @@
+use bevy::asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::{BoxedFuture, HashMap};
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{Coord, Level, TileKind, TileMap};
+
+// Places tried for each prefab before giving up on it
+const ATTEMPTS: u32 = 50;
+
+/// A monster or item a prefab places, by key
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Spawn {
+    Monster(String),
+    Item(String),
+}
+
+/// What a character of a prefab's rows stands for
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PrefabCell {
+    pub tile: TileKind,
+    #[serde(default)]
+    pub spawn: Option<Spawn>,
+}
+
+impl PrefabCell {
+    fn tile(tile: TileKind) -> Self {
+        Self { tile, spawn: None }
+    }
+}
+
+// Characters every prefab can use without a legend, a space keeps the
+// level's own tile
+fn default_legend(character: char) -> Option<Option<PrefabCell>> {
+    let tile = match character {
+        ' ' => return Some(None),
+        '#' => TileKind::Wall,
+        '.' => TileKind::Floor,
+        '+' => TileKind::ClosedDoor,
+        '\'' => TileKind::OpenDoor,
+        '~' => TileKind::Water,
+        _ => return None,
+    };
+    Some(Some(PrefabCell::tile(tile)))
+}
+
+// A prefab as written in a `.prefabs.ron` file
+#[derive(Deserialize)]
+struct PrefabFile {
+    name: String,
+    #[serde(default = "first_depth")]
+    min_depth: u32,
+    #[serde(default = "last_depth")]
+    max_depth: u32,
+    #[serde(default = "one")]
+    weight: u32,
+    rows: Vec<String>,
+    #[serde(default)]
+    legend: HashMap<char, PrefabCell>,
+}
+
+fn first_depth() -> u32 {
+    1
+}
+
+fn last_depth() -> u32 {
+    u32::MAX
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// A hand made room stamped into generated levels between `min_depth` and
+/// `max_depth`, picked by `weight` among the others of its depth
+#[derive(Deserialize, Clone, Debug)]
+#[serde(try_from = "PrefabFile")]
+pub struct Prefab {
+    pub name: String,
+    pub min_depth: u32,
+    pub max_depth: u32,
+    pub weight: u32,
+    width: i32,
+    height: i32,
+    // Row by row, `None` keeps the level's tile
+    cells: Vec<Option<PrefabCell>>,
+}
+
+impl TryFrom<PrefabFile> for Prefab {
+    type Error = String;
+
+    fn try_from(file: PrefabFile) -> Result<Self, Self::Error> {
+        let width = file.rows.first().map_or(0, |row| row.chars().count());
+        if width == 0 || file.rows.iter().any(|row| row.chars().count() != width) {
+            return Err(format!(
+                "the rows of prefab {} are empty or not all as long",
+                file.name
+            ));
+        }
+        let mut cells = Vec::new();
+        for character in file.rows.iter().flat_map(|row| row.chars()) {
+            let cell = match file.legend.get(&character) {
+                Some(cell) => Some(cell.clone()),
+                None => default_legend(character).ok_or_else(|| {
+                    format!("prefab {} has no legend for '{character}'", file.name)
+                })?,
+            };
+            cells.push(cell);
+        }
+        Ok(Self {
+            name: file.name,
+            min_depth: file.min_depth,
+            max_depth: file.max_depth,
+            weight: file.weight,
+            width: width as i32,
+            height: file.rows.len() as i32,
+            cells,
+        })
+    }
+}
+
+impl Prefab {
+    // The prefab turned a quarter clockwise `turns` times, mirrored east to
+    // west first when `mirror`
+    fn transformed(&self, turns: u32, mirror: bool) -> Prefab {
+        let mut prefab = self.clone();
+        if mirror {
+            for row in prefab.cells.chunks_mut(prefab.width as usize) {
+                row.reverse();
+            }
+        }
+        for _ in 0..turns % 4 {
+            let (width, height) = (prefab.height, prefab.width);
+            let mut cells = Vec::with_capacity(prefab.cells.len());
+            for y in 0..height {
+                for x in 0..width {
+                    // Row `y` of the turned prefab is column `y` of the old
+                    // one read from the bottom
+                    let index = (prefab.height - 1 - x) * prefab.width + y;
+                    cells.push(prefab.cells[index as usize].clone());
+                }
+            }
+            prefab.width = width;
+            prefab.height = height;
+            prefab.cells = cells;
+        }
+        prefab
+    }
+
+    // Cells that change the level, with their place when stamped at `at`
+    fn stamped_cells(&self, at: Coord) -> impl Iterator<Item = (Coord, &PrefabCell)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, cell)| {
+                let offset = Coord::new(index as i32 % self.width, index as i32 / self.width);
+                cell.as_ref().map(|cell| (at + offset, cell))
+            })
+    }
+}
+
+/// Every prefab, read from a `.prefabs.ron` file
+#[derive(Asset, TypePath, Deserialize, Clone, Debug, Default)]
+pub struct PrefabLibrary {
+    pub prefabs: Vec<Prefab>,
+}
+
+#[derive(Debug, Error)]
+pub enum PrefabLibraryLoaderError {
+    #[error("could not read prefabs: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse prefabs: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+#[derive(Default)]
+pub struct PrefabLibraryLoader;
+
+impl AssetLoader for PrefabLibraryLoader {
+    type Asset = PrefabLibrary;
+    type Settings = ();
+    type Error = PrefabLibraryLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<PrefabLibrary>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["prefabs.ron"]
+    }
+}
+
+/// Stamp up to `count` prefabs of `depth` into the level, turned and
+/// mirrored at random. A prefab only goes where it overlaps no other
+/// prefab and no stairs, stays inside the walls around the map and leaves
+/// every floor tile reachable from the entrance, including its own.
+pub fn stamp_prefabs(
+    level: &mut Level,
+    library: &PrefabLibrary,
+    depth: u32,
+    count: u32,
+    rng: &mut ChaCha8Rng,
+) {
+    let candidates: Vec<&Prefab> = library
+        .prefabs
+        .iter()
+        .filter(|prefab| (prefab.min_depth..=prefab.max_depth).contains(&depth))
+        .filter(|prefab| prefab.weight > 0)
+        .collect();
+    let total: u32 = candidates.iter().map(|prefab| prefab.weight).sum();
+    if total == 0 {
+        return;
+    }
+    let mut stamped: Vec<(Coord, Coord)> = Vec::new();
+    for _ in 0..count {
+        let mut pick = rng.gen_range(0..total);
+        let Some(prefab) = candidates.iter().find(|prefab| {
+            let found = pick < prefab.weight;
+            pick = pick.saturating_sub(prefab.weight);
+            found
+        }) else {
+            continue;
+        };
+        let prefab = prefab.transformed(rng.gen_range(0..4), rng.gen_bool(0.5));
+        if let Some(area) = stamp(level, &prefab, &stamped, rng) {
+            debug!("Stamped prefab {} at {:?}", prefab.name, area.0);
+            stamped.push(area);
+        }
+    }
+}
+
+// Stamp `prefab` at the first place that keeps the level whole, returns the
+// corners of the area it took
+fn stamp(
+    level: &mut Level,
+    prefab: &Prefab,
+    stamped: &[(Coord, Coord)],
+    rng: &mut ChaCha8Rng,
+) -> Option<(Coord, Coord)> {
+    let (width, height) = (level.map.width() as i32, level.map.height() as i32);
+    // Inside the walls around the map
+    let (max_x, max_y) = (width - 1 - prefab.width, height - 1 - prefab.height);
+    if max_x < 1 || max_y < 1 {
+        return None;
+    }
+    for _ in 0..ATTEMPTS {
+        let at = Coord::new(rng.gen_range(1..=max_x), rng.gen_range(1..=max_y));
+        let end = at + Coord::new(prefab.width - 1, prefab.height - 1);
+        let overlaps = stamped.iter().any(|(start, stop)| {
+            at.x <= stop.x && end.x >= start.x && at.y <= stop.y && end.y >= start.y
+        });
+        let on_stairs = prefab.stamped_cells(at).any(|(coord, _)| {
+            matches!(
+                level.map.kind(coord),
+                TileKind::StairsUp | TileKind::StairsDown
+            )
+        });
+        if overlaps || on_stairs {
+            continue;
+        }
+
+        let mut map = level.map.clone();
+        let mut spawns = Vec::new();
+        for (coord, cell) in prefab.stamped_cells(at) {
+            map.set_kind(coord, cell.tile);
+            if let Some(spawn) = cell.spawn.clone() {
+                spawns.push((coord, spawn));
+            }
+        }
+        if is_connected(&map, level.entrance) {
+            level.map = map;
+            level.spawns.extend(spawns);
+            return Some((at, end));
+        }
+    }
+    None
+}
+
+// Every passable tile can be walked to from `entrance`
+fn is_connected(map: &TileMap, entrance: Coord) -> bool {
+    let reachable = map.walking_distances(entrance).len();
+    let passable = map
+        .iter()
+        .filter(|(_, tile)| tile.kind().is_passable())
+        .count();
+    reachable == passable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::generator::tests::assert_playable;
+    use super::super::{BspGenerator, CaveGenerator, DrunkardGenerator, MapGenerator};
+    use super::*;
+    use rand::SeedableRng;
+
+    fn library() -> PrefabLibrary {
+        let path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/data/vaults.prefabs.ron");
+        ron::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn prefab(rows: &[&str]) -> Prefab {
+        Prefab::try_from(PrefabFile {
+            name: String::from("test"),
+            min_depth: 1,
+            max_depth: 1,
+            weight: 1,
+            rows: rows.iter().map(|row| String::from(*row)).collect(),
+            legend: HashMap::from([(
+                'M',
+                PrefabCell {
+                    tile: TileKind::Floor,
+                    spawn: Some(Spawn::Monster(String::from("ogre"))),
+                },
+            )]),
+        })
+        .unwrap()
+    }
+
+    fn rows(prefab: &Prefab) -> Vec<String> {
+        let character = |cell: &Option<PrefabCell>| match cell {
+            None => ' ',
+            Some(cell) if cell.spawn.is_some() => 'M',
+            Some(cell) if cell.tile == TileKind::Wall => '#',
+            Some(_) => '.',
+        };
+        prefab
+            .cells
+            .chunks(prefab.width as usize)
+            .map(|row| row.iter().map(character).collect())
+            .collect()
+    }
+
+    #[test]
+    fn prefabs_turn_and_mirror() {
+        let prefab = prefab(&["#M.", "## "]);
+        assert_eq!(rows(&prefab.transformed(1, false)), ["##", "#M", " ."]);
+        assert_eq!(rows(&prefab.transformed(2, false)), [" ##", ".M#"]);
+        assert_eq!(rows(&prefab.transformed(0, true)), [".M#", " ##"]);
+        assert_eq!(rows(&prefab.transformed(4, false)), rows(&prefab));
+    }
+
+    #[test]
+    fn prefabs_need_even_rows_and_known_characters() {
+        let file = |rows: &[&str]| PrefabFile {
+            name: String::from("broken"),
+            min_depth: 1,
+            max_depth: 1,
+            weight: 1,
+            rows: rows.iter().map(|row| String::from(*row)).collect(),
+            legend: HashMap::new(),
+        };
+        assert!(Prefab::try_from(file(&["##", "#"])).is_err());
+        assert!(Prefab::try_from(file(&["#X"])).is_err());
+        assert!(Prefab::try_from(file(&[])).is_err());
+    }
+
+    #[test]
+    fn stamped_levels_stay_connected() {
+        let library = library();
+        let generators: [&dyn MapGenerator; 3] = [
+            &BspGenerator::default(),
+            &CaveGenerator::default(),
+            &DrunkardGenerator::default(),
+        ];
+        let mut stamped = 0;
+        for generator in generators {
+            for seed in 0..200 {
+                let mut level = generator.generate(seed);
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                let depth = 1 + seed as u32 % 10;
+                stamp_prefabs(&mut level, &library, depth, 3, &mut rng);
+                assert_playable(&level, seed);
+                stamped += usize::from(!level.spawns.is_empty());
+            }
+        }
+        assert!(stamped > 0, "no prefab with spawns was ever stamped");
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::asset::ron;
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use std::path::PathBuf;
+
+// Name of the folder, or the prefix of the local storage keys, the files
+// are kept in
+const APP_NAME: &str = "roguelike_demo";
+
+#[derive(Debug, Error)]
+pub enum PersistError {
+    #[error("no place to keep files on this platform")]
+    Unavailable,
+    #[error("could not access the file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the settings file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not write the settings file: {0}")]
+    Write(#[from] ron::Error),
+}
+
+/// Read the RON file `name` kept between runs of the game, `None` when it
+/// was never saved or cannot be read
+pub fn load<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let result = read(name).and_then(|text| match text {
+        Some(text) => Ok(Some(ron::from_str(&text)?)),
+        None => Ok(None),
+    });
+    match result {
+        Ok(value) => value,
+        Err(error) => {
+            warn!("Could not load {name}: {error}");
+            None
+        }
+    }
+}
+
+/// Write `value` to the RON file `name`, kept between runs of the game
+pub fn save<T: Serialize>(name: &str, value: &T) {
+    let result = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(PersistError::from)
+        .and_then(|text| write(name, &text));
+    if let Err(error) = result {
+        warn!("Could not save {name}: {error}");
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(name: &str) -> Result<Option<String>, PersistError> {
+    let path = config_dir()?.join(name);
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(name: &str, text: &str) -> Result<(), PersistError> {
+    let dir = config_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    // Write next to the file first so a crash never leaves half of it
+    let temp = dir.join(format!("{name}.tmp"));
+    std::fs::write(&temp, text)?;
+    std::fs::rename(temp, dir.join(name))?;
+    Ok(())
+}
+
+// Folder of the game in the platform's config directory
+#[cfg(not(target_arch = "wasm32"))]
+fn config_dir() -> Result<PathBuf, PersistError> {
+    app_dir("XDG_CONFIG_HOME", ".config")
+}
+
+// Folder of the game in the platform's data directory, the same as the
+// config one except on Linux
+#[cfg(not(target_arch = "wasm32"))]
+fn data_dir() -> Result<PathBuf, PersistError> {
+    app_dir("XDG_DATA_HOME", ".local/share")
+}
+
+// Folder of the game in the platform's directory for apps, `xdg_var` and
+// `home_dir` under the home folder pick it on Linux
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(unused_variables)]
+fn app_dir(xdg_var: &str, home_dir: &str) -> Result<PathBuf, PersistError> {
+    #[cfg(target_os = "android")]
+    let base = bevy::winit::ANDROID_APP
+        .get()
+        .and_then(|app| app.internal_data_path());
+    #[cfg(target_os = "windows")]
+    let base = std::env::var_os("APPDATA").map(PathBuf::from);
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    let base = std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join("Library/Application Support"));
+    #[cfg(not(any(
+        target_os = "android",
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "ios"
+    )))]
+    let base = std::env::var_os(xdg_var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(home_dir)));
+
+    base.map(|base| base.join(APP_NAME))
+        .ok_or(PersistError::Unavailable)
+}
+
+/// Path of the file `name` in the folder `dir` of the game's data
+/// directory, the folder is created if missing
+#[cfg(not(target_arch = "wasm32"))]
+pub fn data_path(dir: &str, name: &str) -> Result<PathBuf, PersistError> {
+    let dir = data_dir()?.join(dir);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join(name))
+}
+
+/// Files are downloaded on the web, the path is only their name
+#[cfg(target_arch = "wasm32")]
+pub fn data_path(_dir: &str, name: &str) -> Result<PathBuf, PersistError> {
+    Ok(PathBuf::from(name))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, PersistError> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or(PersistError::Unavailable)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(name: &str) -> Result<Option<String>, PersistError> {
+    local_storage()?
+        .get_item(&format!("{APP_NAME}/{name}"))
+        .map_err(|_| PersistError::Unavailable)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(name: &str, text: &str) -> Result<(), PersistError> {
+    local_storage()?
+        .set_item(&format!("{APP_NAME}/{name}"), text)
+        .map_err(|_| PersistError::Unavailable)
+}
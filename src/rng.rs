@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// What a random roll is for, every purpose draws from a stream of its own
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RngStream {
+    MapGen,
+    Combat,
+    Loot,
+    Ai,
+}
+
+impl RngStream {
+    const ALL: [RngStream; 4] = [
+        RngStream::MapGen,
+        RngStream::Combat,
+        RngStream::Loot,
+        RngStream::Ai,
+    ];
+}
+
+/// The randomness of the run, seeded once per run. Each `RngStream` is an
+/// independent ChaCha stream, so another roll in one system never shifts
+/// the rolls of the others and a seed plays the same on every platform.
+#[derive(Resource, Clone, Debug)]
+pub struct GameRng {
+    seed: u64,
+    streams: [ChaCha8Rng; 4],
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: RngStream::ALL.map(|stream| sub_stream(seed, stream, 0)),
+        }
+    }
+
+    // Read by saves and replays once they exist
+    #[allow(dead_code)]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The running generator of `stream`
+    // Combat, loot and AI roll from their streams once they exist
+    #[allow(dead_code)]
+    pub fn stream(&mut self, stream: RngStream) -> &mut ChaCha8Rng {
+        &mut self.streams[stream as usize]
+    }
+
+    /// A generator of its own for the level at `depth`, the same whatever
+    /// order the levels are visited in
+    pub fn level(&self, depth: u32) -> ChaCha8Rng {
+        // Index 0 is the running map generation stream
+        sub_stream(self.seed, RngStream::MapGen, u64::from(depth) + 1)
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+// Numbered ChaCha stream `index` of `stream`
+fn sub_stream(seed: u64, stream: RngStream, index: u64) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream((stream as u64) << 32 | index);
+    rng
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn rolls(rng: &mut ChaCha8Rng) -> Vec<u32> {
+        (0..8).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn a_seed_always_rolls_the_same() {
+        let (mut first, mut second) = (GameRng::new(42), GameRng::new(42));
+        for stream in RngStream::ALL {
+            assert_eq!(rolls(first.stream(stream)), rolls(second.stream(stream)));
+        }
+        assert_eq!(rolls(&mut first.level(3)), rolls(&mut second.level(3)));
+        assert_ne!(
+            rolls(GameRng::new(42).stream(RngStream::Combat)),
+            rolls(GameRng::new(43).stream(RngStream::Combat))
+        );
+    }
+
+    #[test]
+    fn rolls_in_one_stream_leave_the_others_alone() {
+        let mut quiet = GameRng::new(7);
+        let mut busy = GameRng::new(7);
+        rolls(busy.stream(RngStream::Combat));
+        rolls(busy.stream(RngStream::Ai));
+        assert_eq!(
+            rolls(quiet.stream(RngStream::Loot)),
+            rolls(busy.stream(RngStream::Loot))
+        );
+        assert_eq!(rolls(&mut quiet.level(1)), rolls(&mut busy.level(1)));
+    }
+
+    #[test]
+    fn streams_and_levels_differ() {
+        let mut rng = GameRng::new(7);
+        let mut seen: Vec<Vec<u32>> = RngStream::ALL
+            .iter()
+            .map(|stream| rolls(rng.stream(*stream)))
+            .collect();
+        seen.extend((1..=5).map(|depth| rolls(&mut rng.level(depth))));
+        for (index, first) in seen.iter().enumerate() {
+            assert!(!seen[index + 1..].contains(first));
+        }
+    }
+}
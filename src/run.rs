@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+
+use crate::gamestate::GameState;
+use crate::rng::GameRng;
+
+/// Statistics of the current run, shown in the run summary once it ends
+#[derive(Resource, Default, Clone, Debug)]
+pub struct RunStats {
+    pub seed: u64,
+    pub depth: u32,
+    pub turns: u32,
+    pub kills: u32,
+    pub cause_of_death: Option<String>,
+}
+
+/// Seed asked for on the command line, used by the next run started without
+/// a seed of its own
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct RequestedSeed(pub u64);
+
+/// Sent to start a new run with the given seed, or a random one
+#[derive(Event)]
+pub struct StartRun {
+    pub seed: Option<u64>,
+}
+
+/// Sent by gameplay when the run is over
+#[derive(Event)]
+pub enum RunEnded {
+    Died {
+        cause: String,
+    },
+    // Sent by gameplay once the last dungeon level exists
+    #[allow(dead_code)]
+    Won,
+}
+
+pub struct RunPlugin;
+
+impl Plugin for RunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .init_resource::<GameRng>()
+            .add_event::<StartRun>()
+            .add_event::<RunEnded>()
+            .add_systems(Update, (start_run, end_run));
+    }
+}
+
+fn start_run(
+    mut commands: Commands,
+    mut start_events: EventReader<StartRun>,
+    requested_seed: Option<Res<RequestedSeed>>,
+    mut run_stats: ResMut<RunStats>,
+    mut game_rng: ResMut<GameRng>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(event) = start_events.read().last() else {
+        return;
+    };
+    let seed = match (event.seed, requested_seed) {
+        (Some(seed), _) => seed,
+        // The requested seed is only played once
+        (None, Some(requested_seed)) => {
+            commands.remove_resource::<RequestedSeed>();
+            requested_seed.0
+        }
+        (None, None) => rand::random(),
+    };
+    info!("Starting a run with seed {seed}");
+    *game_rng = GameRng::new(seed);
+
+    // Runs start on the first level
+    *run_stats = RunStats {
+        seed,
+        depth: 1,
+        ..default()
+    };
+    next_state.set(GameState::GameRunning);
+}
+
+fn end_run(
+    mut end_events: EventReader<RunEnded>,
+    mut run_stats: ResMut<RunStats>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(event) = end_events.read().last() else {
+        return;
+    };
+
+    match event {
+        RunEnded::Died { cause } => {
+            run_stats.cause_of_death = Some(cause.clone());
+            next_state.set(GameState::GameOver);
+        }
+        RunEnded::Won => {
+            next_state.set(GameState::Victory);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use bevy::audio::GlobalVolume;
+use bevy::prelude::*;
+use bevy::window::{MonitorSelection, PresentMode, PrimaryWindow, WindowMode, WindowPosition};
+use serde::{Deserialize, Serialize};
+
+use crate::capture::HeadlessCapture;
+use crate::content::ContentFilter;
+use crate::input::InputBindings;
+use crate::locale::{Language, Locale};
+use crate::persist;
+
+// File the settings are saved to
+const SETTINGS_FILE: &str = "settings.ron";
+
+// Version of the settings file written by this build. Fields added later
+// take their default when an older file is read, bump the version when a
+// field changes meaning and convert older files in `Settings::load`.
+const SETTINGS_VERSION: u32 = 1;
+
+/// Resolutions offered in the settings menu
+pub const RESOLUTIONS: [(u32, u32); 6] = [
+    (1280, 720),
+    (1366, 768),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+    (3840, 2160),
+];
+
+/// Applies the `Settings` as they change and saves them. The language, key
+/// bindings and safe mode are changed through `Locale`, `InputBindings` and
+/// `ContentFilter`.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // Loaded by `main` already when it created the window from them
+        let settings = app
+            .world
+            .get_resource::<Settings>()
+            .cloned()
+            .unwrap_or_else(Settings::load);
+        app.insert_resource(Locale {
+            language: settings.language,
+        })
+        .insert_resource(settings.bindings.clone())
+        .insert_resource(ContentFilter {
+            safe_mode: settings.safe_mode,
+        })
+        .insert_resource(settings)
+        .add_systems(
+            Update,
+            (
+                sync_settings,
+                apply_settings,
+                // Captures run with their own settings
+                save_settings.run_if(not(resource_exists::<HeadlessCapture>)),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DisplayMode {
+    Windowed,
+    #[default]
+    BorderlessFullscreen,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [
+        DisplayMode::Windowed,
+        DisplayMode::BorderlessFullscreen,
+        DisplayMode::Fullscreen,
+    ];
+
+    fn window_mode(self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::BorderlessFullscreen => WindowMode::BorderlessFullscreen,
+            // Fullscreen at the chosen resolution
+            DisplayMode::Fullscreen => WindowMode::SizedFullscreen,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub mode: DisplayMode,
+    // Size of the window, or of the screen in fullscreen
+    pub resolution: (u32, u32),
+    // Where the window was last moved to, it is centred on the monitor
+    // until then
+    pub position: Option<(i32, i32)>,
+    // Index of the monitor the game is shown on, the primary one if `None`
+    pub monitor: Option<usize>,
+    pub vsync: bool,
+    // Keep the game view at its aspect ratio with black bars around it
+    pub letterbox: bool,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            mode: DisplayMode::default(),
+            resolution: (1280, 720),
+            position: None,
+            monitor: None,
+            vsync: true,
+            letterbox: false,
+        }
+    }
+}
+
+impl DisplaySettings {
+    fn apply(&self, window: &mut Window) {
+        let (width, height) = self.resolution;
+        let monitor = match self.monitor {
+            Some(index) => MonitorSelection::Index(index),
+            None => MonitorSelection::Primary,
+        };
+        window.mode = self.mode.window_mode();
+        window.resizable = self.mode == DisplayMode::Windowed;
+        window.resolution.set(width as f32, height as f32);
+        // Fullscreen covers the monitor the window is on
+        window.position = match (self.mode, self.position) {
+            (DisplayMode::Windowed, Some((x, y))) => WindowPosition::At(IVec2::new(x, y)),
+            _ => WindowPosition::Centered(monitor),
+        };
+        window.present_mode = if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+}
+
+/// Volumes from 0 to 1, music and effects are played at their volume times
+/// the master volume
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub effects: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.8,
+            effects: 0.8,
+        }
+    }
+}
+
+/// Everything the player sets in the settings menu, saved whenever it is
+/// changed
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub display: DisplaySettings,
+    pub ui_scale: f32,
+    pub language: Language,
+    pub audio: AudioSettings,
+    pub bindings: InputBindings,
+    // Version of the disclaimer text the player accepted, if any
+    pub accepted_disclaimer: Option<u32>,
+    pub safe_mode: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            display: DisplaySettings::default(),
+            ui_scale: 1.0,
+            language: Language::default(),
+            audio: AudioSettings::default(),
+            bindings: InputBindings::default(),
+            accepted_disclaimer: None,
+            safe_mode: ContentFilter::default().safe_mode,
+        }
+    }
+}
+
+impl Settings {
+    /// The saved settings, or the defaults when there are none
+    pub fn load() -> Self {
+        let Some(mut settings) = persist::load::<Settings>(SETTINGS_FILE) else {
+            return Self::default();
+        };
+        if settings.version > SETTINGS_VERSION {
+            warn!(
+                "{SETTINGS_FILE} is of version {}, newer than {SETTINGS_VERSION}, using the default settings",
+                settings.version
+            );
+            return Self::default();
+        }
+        settings.version = SETTINGS_VERSION;
+        settings
+    }
+
+    /// The primary window as set in the display settings
+    pub fn window(&self) -> Window {
+        let mut window = Window::default();
+        self.display.apply(&mut window);
+        window
+    }
+}
+
+fn sync_settings(
+    locale: Res<Locale>,
+    bindings: Res<InputBindings>,
+    filter: Res<ContentFilter>,
+    mut settings: ResMut<Settings>,
+) {
+    if locale.is_changed() && settings.language != locale.language {
+        settings.language = locale.language;
+    }
+    if bindings.is_changed() && settings.bindings != *bindings {
+        settings.bindings = bindings.clone();
+    }
+    if filter.is_changed() && settings.safe_mode != filter.safe_mode {
+        settings.safe_mode = filter.safe_mode;
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut applied_display: Local<Option<DisplaySettings>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut global_volume: ResMut<GlobalVolume>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    // Only touch the window when the display settings change
+    if applied_display.as_ref() != Some(&settings.display) {
+        if let Ok(mut window) = window_query.get_single_mut() {
+            settings.display.apply(&mut window);
+        }
+        *applied_display = Some(settings.display.clone());
+    }
+    *global_volume = GlobalVolume::new(settings.audio.master);
+}
+
+fn save_settings(settings: Res<Settings>) {
+    if settings.is_changed() && !settings.is_added() {
+        persist::save(SETTINGS_FILE, &*settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{GameAction, InputBinding};
+    use bevy::asset::ron;
+
+    #[test]
+    fn saved_settings_are_read_back_unchanged() {
+        let mut settings = Settings::default();
+        settings.display.mode = DisplayMode::Windowed;
+        settings.display.position = Some((40, -20));
+        settings.audio.music = 0.25;
+        settings.accepted_disclaimer = Some(1);
+        settings.safe_mode = true;
+        settings
+            .bindings
+            .bind(GameAction::Screenshot, InputBinding::Key(KeyCode::F11));
+        settings.bindings.clear(GameAction::Wait);
+
+        let text =
+            ron::ser::to_string_pretty(&settings, ron::ser::PrettyConfig::default()).unwrap();
+        let read: Settings = ron::from_str(&text).unwrap();
+        assert_eq!(read, settings);
+    }
+
+    #[test]
+    fn bindings_saved_before_an_action_existed_get_its_default() {
+        let text = ron::ser::to_string(&Settings::default()).unwrap();
+        let screenshot = "Screenshot:[Key(F12)]";
+        assert!(text.contains(screenshot), "{text}");
+        // As written before there was a screenshot action
+        let old = text
+            .replace(&format!("{screenshot},"), "")
+            .replace(&format!(",{screenshot}"), "");
+        assert!(!old.contains("Screenshot"), "{old}");
+
+        let read: Settings = ron::from_str(&old).unwrap();
+        assert_eq!(
+            read.bindings.get(GameAction::Screenshot),
+            [InputBinding::Key(KeyCode::F12)]
+        );
+        assert_eq!(read, Settings::default());
+    }
+}
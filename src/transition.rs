@@ -0,0 +1,387 @@
+use bevy::input::touch::Touch;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::ui::{FocusPolicy, UiSystem};
+use bevy::window::PrimaryWindow;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::gamestate::GameState;
+
+/// Every `NextState<GameState>` change goes through a screen transition. The
+/// change is held back until the screen is covered at the midpoint of the
+/// transition, then the new state is revealed.
+pub struct ScreenTransitionPlugin;
+
+impl Plugin for ScreenTransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenTransitions>()
+            .init_resource::<ActiveTransition>()
+            // Before `StateTransition` applies the next state
+            .add_systems(PreUpdate, run_transition.in_set(ScreenTransitionSet))
+            .add_systems(
+                PreUpdate,
+                block_input
+                    .after(InputSystem)
+                    .before(UiSystem::Focus)
+                    .run_if(transition_running),
+            );
+    }
+}
+
+/// Systems taking `NextState<GameState>` changes over for a transition
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct ScreenTransitionSet;
+
+#[derive(Clone, Copy, Debug)]
+pub enum TransitionEffect {
+    /// Fade out to the colour and back in from it
+    FadeToColor(Color),
+    /// Freeze the old screen and blend it into the new one
+    Crossfade,
+    /// Sweep the colour over the screen from left to right and off again
+    Wipe(Color),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Transition {
+    pub effect: TransitionEffect,
+    pub duration: Duration,
+}
+
+/// Transitions used for `GameState` changes
+#[derive(Resource)]
+pub struct ScreenTransitions {
+    pub default: Transition,
+    /// Used once for the next change instead of `default`
+    pub next: Option<Transition>,
+}
+
+impl Default for ScreenTransitions {
+    fn default() -> Self {
+        Self {
+            default: Transition {
+                effect: TransitionEffect::FadeToColor(Color::BLACK),
+                duration: Duration::from_millis(600),
+            },
+            next: None,
+        }
+    }
+}
+
+// Screenshot of the old screen for a crossfade, filled in by the renderer
+type Snapshot = Arc<Mutex<Option<Image>>>;
+
+struct RunningTransition {
+    transition: Transition,
+    target: GameState,
+    elapsed: Duration,
+    // Whether the state change was fired at the midpoint
+    fired: bool,
+    // Change asked for after the midpoint, started once this one is done
+    queued: Option<GameState>,
+    // Screenshot requested for a crossfade, not captured yet
+    snapshot: Option<Snapshot>,
+    overlay: Entity,
+}
+
+#[derive(Resource, Default)]
+struct ActiveTransition(Option<RunningTransition>);
+
+#[derive(Component)]
+struct TransitionOverlay;
+
+fn transition_running(active: Res<ActiveTransition>) -> bool {
+    active.0.is_some()
+}
+
+// Nothing may react to input while the screen is changing, the overlay
+// already keeps the pointer away from the UI below
+fn block_input(
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut mouse_buttons: ResMut<ButtonInput<MouseButton>>,
+    mut gamepad_buttons: ResMut<ButtonInput<GamepadButton>>,
+    mut touches: ResMut<Touches>,
+) {
+    keys.reset_all();
+    mouse_buttons.reset_all();
+    gamepad_buttons.reset_all();
+    // Held touches stay pressed so their release is still seen
+    let tapped: Vec<u64> = touches.iter_just_pressed().map(Touch::id).collect();
+    for id in tapped {
+        touches.clear_just_pressed(id);
+    }
+}
+
+/// Take over `GameState` changes, animate the overlay and fire the change
+/// at the midpoint
+#[allow(clippy::too_many_arguments)]
+fn run_transition(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    game_state: Res<State<GameState>>,
+    mut transitions: ResMut<ScreenTransitions>,
+    mut active: ResMut<ActiveTransition>,
+    time: Res<Time<Real>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut images: ResMut<Assets<Image>>,
+    mut overlay_query: Query<
+        (&mut Style, &mut BackgroundColor, &mut UiImage),
+        With<TransitionOverlay>,
+    >,
+) {
+    if let Some(target) = next_state.0.take() {
+        match active.0.as_mut() {
+            // A newer change replaces the one not fired yet
+            Some(running) if !running.fired => running.target = target,
+            // A state left as soon as it is entered is never revealed, the
+            // newer change is fired behind the cover
+            Some(running) if running.elapsed <= running.transition.duration / 2 => {
+                running.target = target.clone();
+                next_state.set(target);
+            }
+            Some(running) => running.queued = Some(target),
+            None => {
+                let transition = transitions.next.take().unwrap_or(transitions.default);
+                active.0 = Some(start_transition(
+                    &mut commands,
+                    &mut screenshots,
+                    &window_query,
+                    transition,
+                    target,
+                ));
+            }
+        }
+    }
+    let Some(running) = active.0.as_mut() else {
+        return;
+    };
+    let Ok((mut style, mut background, mut image)) = overlay_query.get_mut(running.overlay) else {
+        // The overlay is spawned by this frame's commands
+        return;
+    };
+
+    // A crossfade starts once the old screen is captured
+    if let Some(snapshot) = running.snapshot.as_ref() {
+        let Some(captured) = snapshot.lock().unwrap().take() else {
+            return;
+        };
+        image.texture = images.add(captured);
+        running.snapshot = None;
+    }
+
+    let half = running.transition.duration / 2;
+    running.elapsed += time.delta();
+    if !running.fired && running.elapsed >= half {
+        next_state.set(running.target.clone());
+        running.fired = true;
+    }
+    // Stay covered until the new state is entered, it may wait for assets
+    if running.fired && *game_state.get() != running.target {
+        running.elapsed = half;
+    }
+
+    let progress = if running.transition.duration.is_zero() {
+        1.0
+    } else {
+        (running.elapsed.as_secs_f32() / running.transition.duration.as_secs_f32()).min(1.0)
+    };
+    match running.transition.effect {
+        TransitionEffect::FadeToColor(color) => {
+            let cover = 1.0 - (2.0 * progress - 1.0).abs();
+            *background = color.with_a(color.a() * cover).into();
+        }
+        TransitionEffect::Crossfade => {
+            let cover = (2.0 - 2.0 * progress).min(1.0);
+            *background = Color::WHITE.with_a(cover).into();
+        }
+        TransitionEffect::Wipe(color) => {
+            style.left = Val::Percent(200.0 * progress - 100.0);
+            *background = color.into();
+        }
+    }
+
+    if running.fired && progress >= 1.0 {
+        commands.entity(running.overlay).despawn_recursive();
+        let queued = running.queued.take();
+        active.0 = queued.map(|target| {
+            let transition = transitions.next.take().unwrap_or(transitions.default);
+            start_transition(
+                &mut commands,
+                &mut screenshots,
+                &window_query,
+                transition,
+                target,
+            )
+        });
+    }
+}
+
+fn start_transition(
+    commands: &mut Commands,
+    screenshots: &mut ScreenshotManager,
+    window_query: &Query<Entity, With<PrimaryWindow>>,
+    mut transition: Transition,
+    target: GameState,
+) -> RunningTransition {
+    let mut snapshot = None;
+    if let TransitionEffect::Crossfade = transition.effect {
+        let captured = Snapshot::default();
+        let sender = captured.clone();
+        let requested = window_query.get_single().is_ok_and(|window| {
+            screenshots
+                .take_screenshot(window, move |image| {
+                    *sender.lock().unwrap() = Some(image);
+                })
+                .is_ok()
+        });
+        if requested {
+            snapshot = Some(captured);
+        } else {
+            warn!("Could not capture the screen to crossfade, fading to black instead");
+            transition.effect = TransitionEffect::FadeToColor(Color::BLACK);
+        }
+    }
+
+    let overlay = commands
+        .spawn((
+            TransitionOverlay,
+            ImageBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    // A wipe comes in from the left
+                    left: match transition.effect {
+                        TransitionEffect::Wipe(_) => Val::Percent(-100.0),
+                        _ => Val::Px(0.0),
+                    },
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                background_color: Color::NONE.into(),
+                // Keep the pointer away from the UI below
+                focus_policy: FocusPolicy::Block,
+                // Below the asset set loading overlay
+                z_index: ZIndex::Global(90),
+                ..default()
+            },
+        ))
+        .id();
+
+    RunningTransition {
+        transition,
+        target,
+        elapsed: Duration::ZERO,
+        fired: false,
+        queued: None,
+        snapshot,
+        overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::GameStatePlugin;
+    use crate::input::{ActionState, GameAction, GameInputPlugin};
+    use bevy::input::touch::{TouchInput, TouchPhase};
+    use bevy::input::InputPlugin;
+    use bevy::time::TimeUpdateStrategy;
+
+    const FRAME: Duration = Duration::from_millis(100);
+
+    #[derive(Resource, Default)]
+    struct Entered(u32);
+
+    // App stepping the default 600 ms transition by 100 ms a frame
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins((InputPlugin, GameStatePlugin, ScreenTransitionPlugin))
+            .add_plugins(GameInputPlugin)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+            .init_resource::<ScreenshotManager>()
+            .init_resource::<Assets<Image>>()
+            .init_resource::<Entered>()
+            .add_systems(
+                OnEnter(GameState::MainMenu),
+                |mut entered: ResMut<Entered>| {
+                    entered.0 += 1;
+                },
+            );
+        app.world.spawn((Window::default(), PrimaryWindow));
+        app.update();
+        app
+    }
+
+    fn state(app: &App) -> GameState {
+        app.world.resource::<State<GameState>>().get().clone()
+    }
+
+    fn running(app: &App) -> bool {
+        app.world.resource::<ActiveTransition>().0.is_some()
+    }
+
+    #[test]
+    fn a_state_change_waits_for_the_midpoint_and_happens_once() {
+        let mut app = app();
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::MainMenu);
+
+        // The overlay is spawned on the first frame and covers the screen
+        // after 300 ms
+        for _ in 0..3 {
+            app.update();
+            assert_eq!(state(&app), GameState::AssetLoading);
+            assert!(running(&app));
+        }
+        app.update();
+        assert_eq!(state(&app), GameState::MainMenu);
+
+        for _ in 0..10 {
+            app.update();
+        }
+        assert!(!running(&app));
+        assert_eq!(state(&app), GameState::MainMenu);
+        assert_eq!(app.world.resource::<Entered>().0, 1);
+        let overlays = app
+            .world
+            .query_filtered::<(), With<TransitionOverlay>>()
+            .iter(&app.world)
+            .count();
+        assert_eq!(overlays, 0);
+    }
+
+    #[test]
+    fn taps_during_a_transition_are_ignored() {
+        let mut app = app();
+        let window = app
+            .world
+            .query_filtered::<Entity, With<PrimaryWindow>>()
+            .single(&app.world);
+        let tap = |app: &mut App, id: u64| {
+            app.world.send_event(TouchInput {
+                phase: TouchPhase::Started,
+                position: Vec2::ZERO,
+                window,
+                force: None,
+                id,
+            });
+            app.update();
+            app.world
+                .resource::<ActionState>()
+                .just_pressed(GameAction::MoveNorthWest)
+        };
+        assert!(tap(&mut app, 0));
+
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::MainMenu);
+        app.update();
+        assert!(running(&app));
+        assert!(!tap(&mut app, 1));
+    }
+}
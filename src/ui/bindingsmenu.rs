@@ -0,0 +1,188 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+
+use super::widgets::{BackButton, ButtonActivated, UiTheme, WidgetSystems, Widgets};
+use crate::{
+    assetloader::AssetRegistry,
+    fonts::FontStackText,
+    gamestate::{DespawnOnExit, MainMenuState},
+    input::{GameAction, InputBinding, InputBindings},
+    locale::{tr, Strings},
+};
+
+pub struct BindingsMenuPlugin;
+
+impl Plugin for BindingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>();
+        app.add_systems(OnEnter(MainMenuState::Bindings), spawn_bindings_menu);
+        app.add_systems(OnExit(MainMenuState::Bindings), stop_rebinding);
+        // Before the UI and the menus see the input bound to the action
+        app.add_systems(
+            PreUpdate,
+            capture_binding
+                .after(InputSystem)
+                .before(UiSystem::Focus)
+                .run_if(in_state(MainMenuState::Bindings)),
+        );
+        app.add_systems(
+            Update,
+            (bindings_button_interaction, draw_binding_labels)
+                .chain()
+                .after(WidgetSystems)
+                .run_if(in_state(MainMenuState::Bindings)),
+        );
+    }
+}
+
+// Action bound to the next key or button pressed
+#[derive(Resource, Default)]
+struct Rebinding(Option<GameAction>);
+
+#[derive(Component, Clone, Copy)]
+enum BindingsButton {
+    Rebind(GameAction),
+    Reset,
+    Back,
+}
+
+fn bindings_label(
+    strings: &Strings,
+    bindings: &InputBindings,
+    rebinding: &Rebinding,
+    action: GameAction,
+) -> String {
+    if rebinding.0 == Some(action) {
+        return String::from(strings.get("bindings.waiting"));
+    }
+    let labels: Vec<String> = bindings
+        .get(action)
+        .iter()
+        .map(|binding| binding.label(strings))
+        .collect();
+    if labels.is_empty() {
+        String::from(strings.get("bindings.unbound"))
+    } else {
+        labels.join(" / ")
+    }
+}
+
+fn spawn_bindings_menu(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    registry: Res<AssetRegistry>,
+    strings: Res<Strings>,
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+) {
+    let widgets = Widgets::new(&theme, &registry);
+    widgets.screen(
+        &mut commands,
+        DespawnOnExit(MainMenuState::Bindings),
+        |parent| {
+            widgets.heading(parent, tr("bindings.title"));
+            widgets.panel(parent, Val::Percent(80.0), |parent| {
+                widgets.scroll_view(parent, Val::Vh(50.0), |parent| {
+                    for action in GameAction::ALL {
+                        widgets.row(parent, Val::Percent(100.0), |parent| {
+                            widgets.text(parent, action.name());
+                            let label = bindings_label(&strings, &bindings, &rebinding, action);
+                            widgets.button(parent, label, BindingsButton::Rebind(action));
+                        });
+                    }
+                });
+                widgets.text(parent, tr("bindings.hint"));
+            });
+            widgets.row(parent, Val::Percent(60.0), |parent| {
+                widgets.button(parent, tr("bindings.reset"), BindingsButton::Reset);
+                widgets.button(
+                    parent,
+                    tr("common.back"),
+                    (BindingsButton::Back, BackButton),
+                );
+            });
+        },
+    );
+}
+
+fn stop_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
+
+/// Bind the next key, mouse button or gamepad button to the action being
+/// rebound. Escape cancels and Backspace or Delete leaves the action unbound.
+fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut mouse_buttons: ResMut<ButtonInput<MouseButton>>,
+    mut gamepad_buttons: ResMut<ButtonInput<GamepadButton>>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    let key = keys.get_just_pressed().next().copied();
+    let mouse_button = mouse_buttons.get_just_pressed().next().copied();
+    let gamepad_button = gamepad_buttons.get_just_pressed().next().copied();
+    match (key, mouse_button, gamepad_button) {
+        (Some(KeyCode::Escape), _, _) => {}
+        (Some(KeyCode::Backspace | KeyCode::Delete), _, _) => bindings.clear(action),
+        (Some(key), _, _) => bindings.bind(action, InputBinding::Key(key)),
+        (None, Some(button), _) => bindings.bind(action, InputBinding::Mouse(button)),
+        (None, None, Some(button)) => {
+            bindings.bind(action, InputBinding::Gamepad(button.button_type));
+        }
+        (None, None, None) => return,
+    }
+    rebinding.0 = None;
+
+    // The captured input does not reach the menu
+    keys.reset_all();
+    mouse_buttons.reset_all();
+    gamepad_buttons.reset_all();
+}
+
+fn bindings_button_interaction(
+    mut activated_events: EventReader<ButtonActivated>,
+    button_query: Query<&BindingsButton>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+    mut next_main_menu_state: ResMut<NextState<MainMenuState>>,
+) {
+    for ButtonActivated(entity) in activated_events.read() {
+        let Ok(button) = button_query.get(*entity) else {
+            continue;
+        };
+        match button {
+            BindingsButton::Rebind(action) => rebinding.0 = Some(*action),
+            BindingsButton::Reset => {
+                rebinding.0 = None;
+                bindings.set_if_neq(InputBindings::default());
+            }
+            BindingsButton::Back => next_main_menu_state.set(MainMenuState::Settings),
+        }
+    }
+}
+
+fn draw_binding_labels(
+    strings: Res<Strings>,
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    button_query: Query<(&BindingsButton, &Children)>,
+    mut text_query: Query<&mut FontStackText>,
+) {
+    if !strings.is_changed() && !bindings.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+    for (button, children) in button_query.iter() {
+        let BindingsButton::Rebind(action) = button else {
+            continue;
+        };
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = bindings_label(&strings, &bindings, &rebinding, *action);
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+use super::widgets::{ButtonActivated, UiTheme, WidgetSystems, Widgets};
+use crate::{
+    assetloader::{AssetLoadingState, FailedAssets, RetryAssetLoading},
+    gamestate::DespawnOnExit,
+};
+
+pub struct LoadFailedMenuPlugin;
+
+impl Plugin for LoadFailedMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AssetLoadingState::FailedLoading),
+            spawn_load_failed_menu,
+        );
+        app.add_systems(
+            Update,
+            load_failed_button_interaction
+                .after(WidgetSystems)
+                .run_if(in_state(AssetLoadingState::FailedLoading)),
+        );
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum LoadFailedButton {
+    Retry,
+    Quit,
+}
+
+// The fonts may be what failed to load, so this menu only uses Bevy's
+// default font, which has no CJK glyphs
+fn spawn_load_failed_menu(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    failed_assets: Res<FailedAssets>,
+) {
+    let widgets = Widgets::builtin_font(&theme);
+    widgets.screen(
+        &mut commands,
+        DespawnOnExit(AssetLoadingState::FailedLoading),
+        |parent| {
+            widgets.heading(parent, "Failed to load game assets");
+            // One line per failed asset
+            widgets.panel(parent, Val::Percent(80.0), |parent| {
+                widgets.scroll_view(parent, Val::Px(300.0), |parent| {
+                    for failed in failed_assets.0.iter() {
+                        widgets.rich_text(
+                            parent,
+                            &[
+                                (&format!("{}: ", failed.path), theme.error_text),
+                                (&failed.reason, theme.text),
+                            ],
+                        );
+                    }
+                });
+            });
+            widgets.row(parent, Val::Percent(60.0), |parent| {
+                widgets.button(parent, "Retry", LoadFailedButton::Retry);
+                widgets.button(parent, "Quit", LoadFailedButton::Quit);
+            });
+        },
+    );
+}
+
+fn load_failed_button_interaction(
+    mut activated_events: EventReader<ButtonActivated>,
+    button_query: Query<&LoadFailedButton>,
+    mut retry_events: EventWriter<RetryAssetLoading>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    for ButtonActivated(entity) in activated_events.read() {
+        match button_query.get(*entity) {
+            Ok(LoadFailedButton::Retry) => {
+                retry_events.send(RetryAssetLoading);
+            }
+            Ok(LoadFailedButton::Quit) => {
+                exit_events.send(AppExit);
+            }
+            Err(_) => {}
+        }
+    }
+}
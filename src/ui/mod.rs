@@ -1,2 +1,8 @@
+pub mod bindingsmenu;
 pub mod disclaimermenu;
+pub mod loadfailedmenu;
 pub mod mainmenu;
+pub mod pausemenu;
+pub mod runendmenu;
+pub mod settingsmenu;
+pub mod widgets;
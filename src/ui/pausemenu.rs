@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+
+use super::widgets::{BackButton, ButtonActivated, UiTheme, WidgetSystems, Widgets};
+use crate::{
+    assetloader::AssetRegistry,
+    gamestate::{DespawnOnExit, GameState, PauseMenuState},
+    input::{ActionState, GameAction},
+    locale::{tr, Localized, Strings},
+    run::{RunEnded, RunStats},
+};
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(PauseMenuState::Main), spawn_pause_menu);
+        for (state, title_key, text_key) in PLACEHOLDER_SUB_MENUS {
+            app.add_systems(
+                OnEnter(state),
+                placeholder_submenu(title_key, text_key, state),
+            );
+        }
+        app.add_systems(
+            Update,
+            (
+                open_pause_menu,
+                pause_button_interaction.after(WidgetSystems),
+            )
+                .run_if(in_state(GameState::GameRunning)),
+        );
+    }
+}
+
+// Sub-menus with nothing to show yet, with their title and the text shown
+// in place of their content
+const PLACEHOLDER_SUB_MENUS: [(PauseMenuState, &str, &str); 4] = [
+    (
+        PauseMenuState::Inventory,
+        "pause.inventory",
+        "inventory.empty",
+    ),
+    (PauseMenuState::Skills, "pause.skills", "skills.empty"),
+    (PauseMenuState::Save, "pause.save", "save.empty"),
+    (PauseMenuState::Map, "pause.map", "map.empty"),
+];
+
+#[derive(Component, Clone, Copy)]
+enum PauseButton {
+    Resume,
+    Open(PauseMenuState),
+    Abandon,
+    QuitToMainMenu,
+    // Back button of a pause sub-menu, returning to the pause menu
+    Back,
+}
+
+/// The pause action opens the pause menu and the inventory action opens the
+/// inventory. Escape and the gamepad B button go back from the pause menus
+/// with their back buttons.
+fn open_pause_menu(
+    actions: Res<ActionState>,
+    pause_state: Res<State<PauseMenuState>>,
+    mut next_pause_state: ResMut<NextState<PauseMenuState>>,
+) {
+    if *pause_state.get() != PauseMenuState::Unpaused {
+        return;
+    }
+    if actions.just_pressed(GameAction::Pause) {
+        next_pause_state.set(PauseMenuState::Main);
+    } else if actions.just_pressed(GameAction::Inventory) {
+        next_pause_state.set(PauseMenuState::Inventory);
+    }
+}
+
+fn spawn_pause_menu(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    registry: Res<AssetRegistry>,
+    run_stats: Res<RunStats>,
+) {
+    let widgets = Widgets::new(&theme, &registry);
+    widgets.overlay(
+        &mut commands,
+        DespawnOnExit(PauseMenuState::Main),
+        |parent| {
+            widgets.window(parent, tr("pause.title"), Val::Percent(40.0), |parent| {
+                widgets.column(parent, Val::Percent(100.0), |parent| {
+                    widgets.button(
+                        parent,
+                        tr("pause.resume"),
+                        (PauseButton::Resume, BackButton),
+                    );
+                    widgets.button(
+                        parent,
+                        tr("pause.inventory"),
+                        PauseButton::Open(PauseMenuState::Inventory),
+                    );
+                    widgets.button(
+                        parent,
+                        tr("pause.skills"),
+                        PauseButton::Open(PauseMenuState::Skills),
+                    );
+                    widgets.button(
+                        parent,
+                        tr("pause.save"),
+                        PauseButton::Open(PauseMenuState::Save),
+                    );
+                    widgets.button(
+                        parent,
+                        tr("pause.map"),
+                        PauseButton::Open(PauseMenuState::Map),
+                    );
+                    widgets.button(parent, tr("pause.abandon"), PauseButton::Abandon);
+                    widgets.button(parent, tr("common.main_menu"), PauseButton::QuitToMainMenu);
+                });
+                // For bug reports and replays
+                widgets.text(parent, tr("pause.seed").arg("seed", run_stats.seed));
+            });
+        },
+    );
+}
+
+/// Spawn a pause sub-menu: a window titled `title` with the `content` and a
+/// back button. The sub-menu is despawned on exit of its `state`.
+pub fn spawn_pause_sub_menu(
+    commands: &mut Commands,
+    widgets: Widgets,
+    state: PauseMenuState,
+    title: Localized,
+    content: impl FnOnce(&mut ChildBuilder),
+) {
+    widgets.overlay(commands, DespawnOnExit(state), |parent| {
+        widgets.window(parent, title, Val::Percent(60.0), |parent| {
+            widgets.column(parent, Val::Percent(100.0), |parent| {
+                content(parent);
+                widgets.button(parent, tr("common.back"), (PauseButton::Back, BackButton));
+            });
+        });
+    });
+}
+
+/// System spawning the sub-menu of `state` with only the `text_key` text
+fn placeholder_submenu(
+    title_key: &'static str,
+    text_key: &'static str,
+    state: PauseMenuState,
+) -> impl Fn(Commands, Res<UiTheme>, Res<AssetRegistry>) {
+    move |mut commands: Commands, theme: Res<UiTheme>, registry: Res<AssetRegistry>| {
+        let widgets = Widgets::new(&theme, &registry);
+        spawn_pause_sub_menu(&mut commands, widgets, state, tr(title_key), |parent| {
+            widgets.text(parent, tr(text_key));
+        });
+    }
+}
+
+fn pause_button_interaction(
+    mut activated_events: EventReader<ButtonActivated>,
+    button_query: Query<&PauseButton>,
+    mut next_pause_state: ResMut<NextState<PauseMenuState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut run_ended_events: EventWriter<RunEnded>,
+    strings: Res<Strings>,
+) {
+    for ButtonActivated(entity) in activated_events.read() {
+        let Ok(button) = button_query.get(*entity) else {
+            continue;
+        };
+        match button {
+            PauseButton::Resume => next_pause_state.set(PauseMenuState::Unpaused),
+            PauseButton::Open(sub_menu) => next_pause_state.set(*sub_menu),
+            PauseButton::Abandon => {
+                run_ended_events.send(RunEnded::Died {
+                    cause: String::from(strings.get("run.abandoned")),
+                });
+            }
+            PauseButton::QuitToMainMenu => next_game_state.set(GameState::MainMenu),
+            PauseButton::Back => next_pause_state.set(PauseMenuState::Main),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+
+use super::widgets::{BackButton, ButtonActivated, UiTheme, WidgetSystems, Widgets};
+use crate::{
+    assetloader::AssetRegistry,
+    gamestate::{DespawnOnExit, GameState},
+    locale::tr,
+    run::{RunStats, StartRun},
+};
+
+pub struct RunEndMenuPlugin;
+
+impl Plugin for RunEndMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::GameOver), spawn_run_end_menu);
+        app.add_systems(OnEnter(GameState::Victory), spawn_run_end_menu);
+        app.add_systems(
+            Update,
+            run_end_button_interaction
+                .after(WidgetSystems)
+                .run_if(in_state(GameState::GameOver).or_else(in_state(GameState::Victory))),
+        );
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum RunEndButton {
+    SameSeed,
+    NewSeed,
+    MainMenu,
+}
+
+fn spawn_run_end_menu(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    registry: Res<AssetRegistry>,
+    game_state: Res<State<GameState>>,
+    run_stats: Res<RunStats>,
+) {
+    let title = match game_state.get() {
+        GameState::Victory => tr("run_end.victory"),
+        _ => tr("run_end.died"),
+    };
+
+    // Run summary, one line per statistic
+    let mut lines = vec![
+        tr("run_end.depth").arg("depth", run_stats.depth),
+        tr("run_end.turns").arg("turns", run_stats.turns),
+        tr("run_end.kills").arg("kills", run_stats.kills),
+    ];
+    if let Some(cause) = &run_stats.cause_of_death {
+        lines.push(tr("run_end.cause").arg("cause", cause));
+    }
+    lines.push(tr("run_end.seed").arg("seed", run_stats.seed));
+
+    let widgets = Widgets::new(&theme, &registry);
+    widgets.screen(
+        &mut commands,
+        DespawnOnExit(game_state.get().clone()),
+        |parent| {
+            widgets.title(parent, title);
+            widgets.panel(parent, Val::Percent(80.0), |parent| {
+                for line in lines {
+                    widgets.text(parent, line);
+                }
+            });
+            widgets.column(parent, Val::Percent(60.0), |parent| {
+                widgets.button(parent, tr("run_end.same_seed"), RunEndButton::SameSeed);
+                widgets.button(parent, tr("run_end.new_seed"), RunEndButton::NewSeed);
+                widgets.button(
+                    parent,
+                    tr("common.main_menu"),
+                    (RunEndButton::MainMenu, BackButton),
+                );
+            });
+        },
+    );
+}
+
+fn run_end_button_interaction(
+    mut activated_events: EventReader<ButtonActivated>,
+    button_query: Query<&RunEndButton>,
+    run_stats: Res<RunStats>,
+    mut start_run_events: EventWriter<StartRun>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for ButtonActivated(entity) in activated_events.read() {
+        let Ok(button) = button_query.get(*entity) else {
+            continue;
+        };
+        match button {
+            RunEndButton::SameSeed => {
+                start_run_events.send(StartRun {
+                    seed: Some(run_stats.seed),
+                });
+            }
+            RunEndButton::NewSeed => {
+                start_run_events.send(StartRun { seed: None });
+            }
+            RunEndButton::MainMenu => next_state.set(GameState::MainMenu),
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use bevy::prelude::*;
+use bevy::winit::WinitWindows;
+
+use super::widgets::{
+    BackButton, ButtonActivated, DropdownChanged, Slider, SliderChanged, ToggleChanged, UiTheme,
+    WidgetSystems, Widgets,
+};
+use crate::{
+    assetloader::AssetRegistry,
+    content::{ContentFilter, SAFE_BUILD},
+    gamestate::{DespawnOnExit, GameState, MainMenuState},
+    locale::{tr, Language, Locale, UiText},
+    settings::{DisplayMode, Settings, RESOLUTIONS},
+};
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MainMenuState::Settings), spawn_settings_menu);
+        app.add_systems(
+            Update,
+            (
+                settings_button_interaction,
+                pick_settings,
+                slide_settings,
+                toggle_settings,
+            )
+                .after(WidgetSystems)
+                .run_if(in_state(MainMenuState::Settings)),
+        );
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum SettingsButton {
+    Bindings,
+    RevokeDisclaimer,
+    Back,
+}
+
+#[derive(Component, Clone, Copy)]
+enum SettingsDropdown {
+    DisplayMode,
+    Resolution,
+    Monitor,
+    Language,
+}
+
+// Resolutions of the resolution dropdown, with the size the window was
+// given when it is none of the usual ones
+#[derive(Component)]
+struct ResolutionOptions(Vec<(u32, u32)>);
+
+#[derive(Component, Clone, Copy)]
+enum SettingsSlider {
+    UiScale,
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+}
+
+#[derive(Component, Clone, Copy)]
+enum SettingsToggle {
+    Vsync,
+    Letterbox,
+    SafeMode,
+}
+
+fn display_mode_name(mode: DisplayMode) -> UiText {
+    match mode {
+        DisplayMode::Windowed => tr("settings.windowed"),
+        DisplayMode::BorderlessFullscreen => tr("settings.borderless"),
+        DisplayMode::Fullscreen => tr("settings.fullscreen"),
+    }
+    .into()
+}
+
+// Index of `value` in `options`, the first option when it is not one
+fn position_of<T: PartialEq>(options: &[T], value: &T) -> usize {
+    options
+        .iter()
+        .position(|option| option == value)
+        .unwrap_or_default()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_settings_menu(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    registry: Res<AssetRegistry>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    filter: Res<ContentFilter>,
+    winit_windows: Option<NonSend<WinitWindows>>,
+) {
+    let modes = DisplayMode::ALL
+        .into_iter()
+        .map(display_mode_name)
+        .collect();
+    let mut resolutions = RESOLUTIONS.to_vec();
+    if !resolutions.contains(&settings.display.resolution) {
+        resolutions.push(settings.display.resolution);
+    }
+    let resolution_names = resolutions
+        .iter()
+        .map(|(width, height)| UiText::from(format!("{width} × {height}")))
+        .collect();
+    // The primary monitor, then every monitor by name
+    let mut monitors = vec![UiText::from(tr("settings.primary_monitor"))];
+    let window = winit_windows
+        .as_ref()
+        .and_then(|windows| windows.windows.values().next());
+    if let Some(window) = window {
+        let names = window
+            .available_monitors()
+            .enumerate()
+            .map(|(index, monitor)| {
+                UiText::from(monitor.name().unwrap_or_else(|| format!("{}", index + 1)))
+            });
+        monitors.extend(names);
+    }
+    let monitor = settings.display.monitor.map_or(0, |index| index + 1);
+    let languages = Language::ALL
+        .iter()
+        .map(|language| UiText::from(language.name()))
+        .collect();
+    // Volumes are shown in percent
+    let volume = |value: f32| Slider::new(100.0 * value, 0.0, 100.0, 5.0);
+
+    let widgets = Widgets::new(&theme, &registry);
+    widgets.screen(
+        &mut commands,
+        DespawnOnExit(MainMenuState::Settings),
+        |parent| {
+            widgets.heading(parent, tr("settings.title"));
+            widgets.row(parent, Val::Percent(90.0), |parent| {
+                widgets.panel(parent, Val::Percent(50.0), |parent| {
+                    widgets.row(parent, Val::Percent(100.0), |parent| {
+                        widgets.text(parent, tr("settings.display_mode"));
+                        widgets.dropdown(
+                            parent,
+                            modes,
+                            position_of(&DisplayMode::ALL, &settings.display.mode),
+                            SettingsDropdown::DisplayMode,
+                        );
+                    });
+                    widgets.row(parent, Val::Percent(100.0), |parent| {
+                        widgets.text(parent, tr("settings.resolution"));
+                        widgets.dropdown(
+                            parent,
+                            resolution_names,
+                            position_of(&resolutions, &settings.display.resolution),
+                            (SettingsDropdown::Resolution, ResolutionOptions(resolutions)),
+                        );
+                    });
+                    widgets.row(parent, Val::Percent(100.0), |parent| {
+                        widgets.text(parent, tr("settings.monitor"));
+                        widgets.dropdown(parent, monitors, monitor, SettingsDropdown::Monitor);
+                    });
+                    widgets.toggle(
+                        parent,
+                        tr("settings.vsync"),
+                        settings.display.vsync,
+                        SettingsToggle::Vsync,
+                    );
+                    widgets.toggle(
+                        parent,
+                        tr("settings.letterbox"),
+                        settings.display.letterbox,
+                        SettingsToggle::Letterbox,
+                    );
+                    widgets.slider(
+                        parent,
+                        tr("settings.ui_scale"),
+                        Slider::new(settings.ui_scale, 0.5, 2.0, 0.1),
+                        SettingsSlider::UiScale,
+                    );
+                });
+                widgets.panel(parent, Val::Percent(50.0), |parent| {
+                    widgets.row(parent, Val::Percent(100.0), |parent| {
+                        widgets.text(parent, tr("settings.language"));
+                        widgets.dropdown(
+                            parent,
+                            languages,
+                            position_of(&Language::ALL, &locale.language),
+                            SettingsDropdown::Language,
+                        );
+                    });
+                    widgets.slider(
+                        parent,
+                        tr("settings.master_volume"),
+                        volume(settings.audio.master),
+                        SettingsSlider::MasterVolume,
+                    );
+                    widgets.slider(
+                        parent,
+                        tr("settings.music_volume"),
+                        volume(settings.audio.music),
+                        SettingsSlider::MusicVolume,
+                    );
+                    widgets.slider(
+                        parent,
+                        tr("settings.effects_volume"),
+                        volume(settings.audio.effects),
+                        SettingsSlider::EffectsVolume,
+                    );
+                    // Builds without explicit content are always safe
+                    if !SAFE_BUILD {
+                        widgets.toggle(
+                            parent,
+                            tr("settings.safe_mode"),
+                            filter.safe_mode,
+                            SettingsToggle::SafeMode,
+                        );
+                    }
+                });
+            });
+            widgets.column(parent, Val::Percent(60.0), |parent| {
+                widgets.button(parent, tr("settings.bindings"), SettingsButton::Bindings);
+                widgets.button(
+                    parent,
+                    tr("settings.revoke_disclaimer"),
+                    SettingsButton::RevokeDisclaimer,
+                );
+                widgets.button(
+                    parent,
+                    tr("common.back"),
+                    (SettingsButton::Back, BackButton),
+                );
+            });
+        },
+    );
+}
+
+fn settings_button_interaction(
+    mut activated_events: EventReader<ButtonActivated>,
+    button_query: Query<&SettingsButton>,
+    mut settings: ResMut<Settings>,
+    mut next_main_menu_state: ResMut<NextState<MainMenuState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    for ButtonActivated(entity) in activated_events.read() {
+        match button_query.get(*entity) {
+            Ok(SettingsButton::Bindings) => next_main_menu_state.set(MainMenuState::Bindings),
+            // The disclaimer has to be accepted again to go on playing
+            Ok(SettingsButton::RevokeDisclaimer) => {
+                settings.accepted_disclaimer = None;
+                next_game_state.set(GameState::DisclaimerMenu);
+            }
+            Ok(SettingsButton::Back) => next_main_menu_state.set(MainMenuState::Main),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Apply the picked option at once, the language switches every localized
+/// text to it
+fn pick_settings(
+    mut changed_events: EventReader<DropdownChanged>,
+    dropdown_query: Query<(&SettingsDropdown, Option<&ResolutionOptions>)>,
+    mut settings: ResMut<Settings>,
+    mut locale: ResMut<Locale>,
+) {
+    for event in changed_events.read() {
+        let Ok((dropdown, resolutions)) = dropdown_query.get(event.entity) else {
+            continue;
+        };
+        match dropdown {
+            SettingsDropdown::DisplayMode => {
+                if let Some(mode) = DisplayMode::ALL.get(event.selected) {
+                    settings.display.mode = *mode;
+                }
+            }
+            SettingsDropdown::Resolution => {
+                let resolution = resolutions.and_then(|options| options.0.get(event.selected));
+                if let Some(resolution) = resolution {
+                    settings.display.resolution = *resolution;
+                }
+            }
+            // The window is centred on the new monitor
+            SettingsDropdown::Monitor => {
+                settings.display.monitor = event.selected.checked_sub(1);
+                settings.display.position = None;
+            }
+            SettingsDropdown::Language => {
+                if let Some(language) = Language::ALL.get(event.selected) {
+                    locale.language = *language;
+                }
+            }
+        }
+    }
+}
+
+fn slide_settings(
+    mut changed_events: EventReader<SliderChanged>,
+    slider_query: Query<&SettingsSlider>,
+    mut settings: ResMut<Settings>,
+) {
+    for event in changed_events.read() {
+        match slider_query.get(event.entity) {
+            Ok(SettingsSlider::UiScale) => settings.ui_scale = event.value,
+            Ok(SettingsSlider::MasterVolume) => settings.audio.master = event.value / 100.0,
+            Ok(SettingsSlider::MusicVolume) => settings.audio.music = event.value / 100.0,
+            Ok(SettingsSlider::EffectsVolume) => settings.audio.effects = event.value / 100.0,
+            Err(_) => {}
+        }
+    }
+}
+
+fn toggle_settings(
+    mut changed_events: EventReader<ToggleChanged>,
+    toggle_query: Query<&SettingsToggle>,
+    mut settings: ResMut<Settings>,
+    mut filter: ResMut<ContentFilter>,
+) {
+    for event in changed_events.read() {
+        match toggle_query.get(event.entity) {
+            Ok(SettingsToggle::Vsync) => settings.display.vsync = event.on,
+            Ok(SettingsToggle::Letterbox) => settings.display.letterbox = event.on,
+            Ok(SettingsToggle::SafeMode) => filter.safe_mode = event.on,
+            Err(_) => {}
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+
+use super::{Focusable, UiTheme, Widgets};
+use crate::fonts::FontStackText;
+use crate::locale::UiText;
+
+/// A themed button, activated when released after being pressed
+#[derive(Component, Default)]
+pub struct WidgetButton {
+    // Disabled buttons are greyed out and never activated
+    pub disabled: bool,
+    pressed: bool,
+}
+
+/// Sent when a `WidgetButton` is activated
+#[derive(Event)]
+pub struct ButtonActivated(pub Entity);
+
+impl Widgets<'_> {
+    /// Spawn a button labelled `label`, with `bundle` telling the menu what
+    /// it does
+    pub fn button(
+        &self,
+        parent: &mut ChildBuilder,
+        label: impl Into<UiText>,
+        bundle: impl Bundle,
+    ) -> Entity {
+        parent
+            .spawn((
+                bundle,
+                Focusable,
+                WidgetButton::default(),
+                self.button_bundle(),
+            ))
+            .with_children(|parent| {
+                self.button_label(parent, label.into());
+            })
+            .id()
+    }
+
+    pub(super) fn button_bundle(&self) -> ButtonBundle {
+        ButtonBundle {
+            style: Style {
+                height: Val::Px(self.theme.button_height),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(self.theme.spacing),
+                ..default()
+            },
+            background_color: self.theme.button.into(),
+            ..default()
+        }
+    }
+
+    pub(super) fn button_label(&self, parent: &mut ChildBuilder, label: UiText) {
+        let (size, color, font) = (
+            self.theme.text_size,
+            self.theme.button_text,
+            self.theme.text_fonts,
+        );
+        self.spawn_ui_text(parent, label, size, color, font);
+    }
+}
+
+/// Show the state of the buttons and activate the released ones
+#[allow(clippy::type_complexity)]
+pub(super) fn update_buttons(
+    theme: Res<UiTheme>,
+    mut button_query: Query<
+        (
+            Entity,
+            &Interaction,
+            &mut WidgetButton,
+            &mut BackgroundColor,
+            &Children,
+        ),
+        Or<(Changed<Interaction>, Changed<WidgetButton>)>,
+    >,
+    mut text_query: Query<&mut FontStackText>,
+    mut activated_events: EventWriter<ButtonActivated>,
+) {
+    for (entity, interact, mut button, mut backgroundcolor, children) in &mut button_query {
+        // Only the pressed flag changes here, do not wake this system again
+        let button = button.bypass_change_detection();
+        let text_color = if button.disabled {
+            *backgroundcolor = theme.button_disabled.into();
+            button.pressed = false;
+            theme.button_text_disabled
+        } else {
+            match interact {
+                Interaction::Pressed => {
+                    *backgroundcolor = theme.button_pressed.into();
+                    button.pressed = true;
+                }
+                // Touches leave the button when released, so any release
+                // activates it
+                _ => {
+                    *backgroundcolor = match interact {
+                        Interaction::Hovered => theme.button_hovered.into(),
+                        _ => theme.button.into(),
+                    };
+                    if button.pressed {
+                        activated_events.send(ButtonActivated(entity));
+                    }
+                    button.pressed = false;
+                }
+            }
+            theme.button_text
+        };
+
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                for section in text.sections.iter_mut() {
+                    section.style.color = text_color;
+                }
+            }
+        }
+    }
+}
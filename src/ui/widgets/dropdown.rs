@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+
+use super::{ButtonActivated, Focus, Focusable, WidgetButton, Widgets};
+use crate::fonts::FontStackText;
+use crate::locale::{Strings, UiText};
+
+/// A button showing the selected option, opening the list of options
+#[derive(Component, Clone, Debug)]
+pub struct Dropdown {
+    pub options: Vec<UiText>,
+    pub selected: usize,
+    pub open: bool,
+}
+
+/// Sent when another option of a `Dropdown` is picked by the player
+#[derive(Event)]
+pub struct DropdownChanged {
+    pub entity: Entity,
+    pub selected: usize,
+}
+
+#[derive(Component)]
+pub(super) struct DropdownButton;
+
+#[derive(Component)]
+pub(super) struct DropdownList;
+
+#[derive(Component)]
+pub(super) struct DropdownOption {
+    pub(super) dropdown: Entity,
+    // Focused again once the option is picked
+    pub(super) button: Entity,
+    index: usize,
+}
+
+impl Widgets<'_> {
+    /// Spawn a dropdown, with `bundle` put next to the `Dropdown` and the
+    /// events sent for its entity
+    pub fn dropdown(
+        &self,
+        parent: &mut ChildBuilder,
+        options: Vec<UiText>,
+        selected: usize,
+        bundle: impl Bundle,
+    ) -> Entity {
+        let mut dropdown = parent.spawn((
+            bundle,
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Stretch,
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+        let entity = dropdown.id();
+        dropdown.with_children(|parent| {
+            let button = parent
+                .spawn((
+                    DropdownButton,
+                    Focusable,
+                    WidgetButton::default(),
+                    self.button_bundle(),
+                ))
+                .with_children(|parent| {
+                    // Filled in with the selected option once it is drawn
+                    self.button_label(parent, "".into());
+                })
+                .id();
+            // The list opens over the widgets below the dropdown
+            parent
+                .spawn((
+                    DropdownList,
+                    NodeBundle {
+                        style: Style {
+                            display: Display::None,
+                            position_type: PositionType::Absolute,
+                            top: Val::Percent(100.0),
+                            width: Val::Percent(100.0),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Stretch,
+                            ..default()
+                        },
+                        background_color: self.theme.panel.into(),
+                        z_index: ZIndex::Global(10),
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    for (index, option) in options.iter().enumerate() {
+                        self.button(
+                            parent,
+                            option.clone(),
+                            DropdownOption {
+                                dropdown: entity,
+                                button,
+                                index,
+                            },
+                        );
+                    }
+                });
+        });
+        dropdown.insert(Dropdown {
+            options,
+            selected,
+            open: false,
+        });
+        entity
+    }
+}
+
+fn dropdown_label(strings: &Strings, options: &[UiText], selected: usize) -> String {
+    let option = match options.get(selected) {
+        Some(UiText::Localized(localized)) => strings.format(localized),
+        Some(UiText::Raw(value)) => value.clone(),
+        None => String::new(),
+    };
+    format!("{option} ▼")
+}
+
+/// Open a dropdown when its button is activated, and pick the activated
+/// option
+pub(super) fn pick_dropdowns(
+    mut activated_events: EventReader<ButtonActivated>,
+    button_query: Query<&Parent, With<DropdownButton>>,
+    option_query: Query<&DropdownOption>,
+    mut dropdown_query: Query<&mut Dropdown>,
+    mut focus: ResMut<Focus>,
+    mut changed_events: EventWriter<DropdownChanged>,
+) {
+    for ButtonActivated(entity) in activated_events.read() {
+        if let Ok(parent) = button_query.get(*entity) {
+            if let Ok(mut dropdown) = dropdown_query.get_mut(parent.get()) {
+                dropdown.open = !dropdown.open;
+            }
+        }
+        if let Ok(option) = option_query.get(*entity) {
+            if let Ok(mut dropdown) = dropdown_query.get_mut(option.dropdown) {
+                dropdown.open = false;
+                focus.set(option.button);
+                if dropdown.selected != option.index {
+                    dropdown.selected = option.index;
+                    changed_events.send(DropdownChanged {
+                        entity: option.dropdown,
+                        selected: option.index,
+                    });
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn draw_dropdowns(
+    strings: Res<Strings>,
+    dropdown_query: Query<(Ref<Dropdown>, &Children)>,
+    mut list_query: Query<&mut Style, With<DropdownList>>,
+    button_query: Query<&Children, With<DropdownButton>>,
+    mut text_query: Query<&mut FontStackText>,
+) {
+    for (dropdown, children) in dropdown_query.iter() {
+        if !dropdown.is_changed() && !strings.is_changed() {
+            continue;
+        }
+        for &child in children.iter() {
+            if let Ok(mut style) = list_query.get_mut(child) {
+                style.display = if dropdown.open {
+                    Display::Flex
+                } else {
+                    Display::None
+                };
+            }
+            let Ok(labels) = button_query.get(child) else {
+                continue;
+            };
+            for &label in labels.iter() {
+                if let Ok(mut text) = text_query.get_mut(label) {
+                    text.sections[0].value =
+                        dropdown_label(&strings, &dropdown.options, dropdown.selected);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use bevy::prelude::*;
+
+use super::dropdown::{Dropdown, DropdownOption};
+use super::slider::Slider;
+use super::{ButtonActivated, SliderChanged, UiTheme, WidgetButton};
+
+/// Widget reachable with the arrow keys and the D-pad
+#[derive(Component, Default)]
+pub struct Focusable;
+
+/// Root of a menu, its focusable widgets are visited in the order they are
+/// laid out and focus wraps around from the last one to the first
+#[derive(Component, Default)]
+pub struct FocusScope;
+
+/// Button activated by Escape or the gamepad B button in its menu
+#[derive(Component)]
+pub struct BackButton;
+
+/// The focused widget, in the menu spawned last
+#[derive(Resource, Default)]
+pub struct Focus {
+    pub entity: Option<Entity>,
+    scope: Option<Entity>,
+}
+
+impl Focus {
+    pub fn set(&mut self, entity: Entity) {
+        self.entity = Some(entity);
+    }
+}
+
+// Menu inputs read from the keyboard and the gamepads
+#[derive(Default)]
+struct MenuInput {
+    previous: bool,
+    next: bool,
+    decrease: bool,
+    increase: bool,
+    activate: bool,
+    back: bool,
+}
+
+impl MenuInput {
+    fn read(
+        keys: &ButtonInput<KeyCode>,
+        gamepads: &Gamepads,
+        gamepad_buttons: &ButtonInput<GamepadButton>,
+    ) -> Self {
+        let pad = |button_type| {
+            gamepads.iter().any(|gamepad| {
+                gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type))
+            })
+        };
+        Self {
+            previous: keys.just_pressed(KeyCode::ArrowUp) || pad(GamepadButtonType::DPadUp),
+            next: keys.just_pressed(KeyCode::ArrowDown) || pad(GamepadButtonType::DPadDown),
+            decrease: keys.just_pressed(KeyCode::ArrowLeft) || pad(GamepadButtonType::DPadLeft),
+            increase: keys.just_pressed(KeyCode::ArrowRight) || pad(GamepadButtonType::DPadRight),
+            activate: keys.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter])
+                || pad(GamepadButtonType::South),
+            back: keys.just_pressed(KeyCode::Escape) || pad(GamepadButtonType::East),
+        }
+    }
+}
+
+// Focusable widgets of the scope in layout order, hidden ones are skipped
+fn focus_order(
+    scope: Entity,
+    children_query: &Query<&Children>,
+    node_query: &Query<(&Node, &Style, Has<Focusable>)>,
+) -> Vec<Entity> {
+    let mut order = Vec::new();
+    let mut stack = vec![scope];
+    while let Some(entity) = stack.pop() {
+        let Ok((node, style, focusable)) = node_query.get(entity) else {
+            continue;
+        };
+        if style.display == Display::None || node.size() == Vec2::ZERO {
+            continue;
+        }
+        if focusable {
+            order.push(entity);
+        }
+        if let Ok(children) = children_query.get(entity) {
+            stack.extend(children.iter().rev());
+        }
+    }
+    order
+}
+
+/// Move the focus with the arrow keys and the D-pad, follow the pointer and
+/// activate the focused widget
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(super) fn navigate_focus(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut focus: ResMut<Focus>,
+    new_scope_query: Query<Entity, Added<FocusScope>>,
+    scope_query: Query<Entity, With<FocusScope>>,
+    hovered_query: Query<(Entity, &Interaction), (Changed<Interaction>, With<Focusable>)>,
+    children_query: Query<&Children>,
+    node_query: Query<(&Node, &Style, Has<Focusable>)>,
+    mut widget_query: Query<(
+        Option<&WidgetButton>,
+        Option<&mut Slider>,
+        Option<&DropdownOption>,
+        Option<&Parent>,
+    )>,
+    mut dropdown_query: Query<&mut Dropdown>,
+    back_query: Query<Entity, With<BackButton>>,
+    mut activated_events: EventWriter<ButtonActivated>,
+    mut slider_events: EventWriter<SliderChanged>,
+) {
+    // A new menu takes the focus, a closed one gives it back to another
+    if let Some(scope) = new_scope_query.iter().last() {
+        focus.scope = Some(scope);
+        focus.entity = None;
+    } else if !focus.scope.is_some_and(|scope| scope_query.contains(scope)) {
+        focus.scope = scope_query.iter().next();
+        focus.entity = None;
+    }
+    let Some(scope) = focus.scope else {
+        return;
+    };
+
+    // Hovering a widget focuses it
+    for (entity, interact) in hovered_query.iter() {
+        if *interact != Interaction::None {
+            focus.entity = Some(entity);
+        }
+    }
+
+    let order = focus_order(scope, &children_query, &node_query);
+    let Some(&first) = order.first() else {
+        return;
+    };
+    let position = focus
+        .entity
+        .and_then(|entity| order.iter().position(|&focused| focused == entity));
+    let Some(position) = position else {
+        focus.entity = Some(first);
+        return;
+    };
+    let focused = order[position];
+
+    let input = MenuInput::read(&keys, &gamepads, &gamepad_buttons);
+    if input.previous {
+        focus.entity = Some(order[(position + order.len() - 1) % order.len()]);
+    }
+    if input.next {
+        focus.entity = Some(order[(position + 1) % order.len()]);
+    }
+
+    let Ok((button, slider, option, parent)) = widget_query.get_mut(focused) else {
+        return;
+    };
+    if let Some(mut slider) = slider {
+        let step = match (input.decrease, input.increase) {
+            (true, false) => -slider.step,
+            (false, true) => slider.step,
+            _ => 0.0,
+        };
+        let value = slider.snap(slider.value + step);
+        if value != slider.value {
+            slider.value = value;
+            slider_events.send(SliderChanged {
+                entity: focused,
+                value,
+            });
+        }
+    } else if input.decrease {
+        focus.entity = Some(order[(position + order.len() - 1) % order.len()]);
+    } else if input.increase {
+        focus.entity = Some(order[(position + 1) % order.len()]);
+    }
+
+    if input.activate && button.is_some_and(|button| !button.disabled) {
+        activated_events.send(ButtonActivated(focused));
+    }
+
+    if input.back {
+        // Close the open dropdown of the focused button or option first
+        let dropdown = option
+            .map(|option| option.dropdown)
+            .or(parent.map(|parent| parent.get()));
+        let open_dropdown = dropdown
+            .and_then(|dropdown| dropdown_query.get_mut(dropdown).ok())
+            .filter(|dropdown| dropdown.open);
+        if let Some(mut dropdown) = open_dropdown {
+            dropdown.open = false;
+            if let Some(option) = option {
+                focus.entity = Some(option.button);
+            }
+        } else if let Some(back) = back_query.iter().find(|back| order.contains(back)) {
+            activated_events.send(ButtonActivated(back));
+        }
+    }
+}
+
+/// Outline the focused widget
+pub(super) fn draw_focus(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    focus: Res<Focus>,
+    mut outlined: Local<Option<Entity>>,
+) {
+    if !focus.is_changed() || *outlined == focus.entity {
+        return;
+    }
+    if let Some(entity) = outlined.take() {
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.remove::<Outline>();
+        }
+    }
+    if let Some(entity) = focus.entity {
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.insert(Outline::new(
+                Val::Px(3.0),
+                Val::Px(2.0),
+                theme.focus_outline,
+            ));
+            *outlined = Some(entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::input::gamepad::{
+        gamepad_connection_system, GamepadConnection, GamepadConnectionEvent, GamepadInfo,
+    };
+    use bevy::reflect::Struct;
+
+    const PAD: Gamepad = Gamepad { id: 0 };
+
+    // Layout does not run headless, the node is given its size directly
+    fn laid_out() -> Node {
+        let mut node = Node::default();
+        node.field_mut("calculated_size")
+            .unwrap()
+            .apply(&Vec2::new(100.0, 20.0));
+        node
+    }
+
+    // App with a menu of three buttons, the last one going back
+    fn app() -> (App, [Entity; 3]) {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<ButtonActivated>()
+            .add_event::<SliderChanged>()
+            .add_event::<GamepadConnectionEvent>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<Gamepads>()
+            .init_resource::<ButtonInput<GamepadButton>>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .init_resource::<Axis<GamepadButton>>()
+            .init_resource::<Focus>()
+            .add_systems(PreUpdate, gamepad_connection_system)
+            .add_systems(Update, navigate_focus);
+        app.world.send_event(GamepadConnectionEvent::new(
+            PAD,
+            GamepadConnection::Connected(GamepadInfo {
+                name: String::from("Pad"),
+            }),
+        ));
+
+        let button = || {
+            (
+                Focusable,
+                WidgetButton::default(),
+                laid_out(),
+                Style::default(),
+            )
+        };
+        let buttons = [
+            app.world.spawn(button()).id(),
+            app.world.spawn(button()).id(),
+            app.world.spawn((button(), BackButton)).id(),
+        ];
+        app.world
+            .spawn((FocusScope, laid_out(), Style::default()))
+            .push_children(&buttons);
+        app.update();
+        (app, buttons)
+    }
+
+    fn press_key(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<ButtonInput<KeyCode>>().press(key);
+        app.update();
+        let mut keys = app.world.resource_mut::<ButtonInput<KeyCode>>();
+        keys.release(key);
+        keys.clear();
+    }
+
+    fn press_pad(app: &mut App, button_type: GamepadButtonType) {
+        let button = GamepadButton::new(PAD, button_type);
+        app.world
+            .resource_mut::<ButtonInput<GamepadButton>>()
+            .press(button);
+        app.update();
+        let mut buttons = app.world.resource_mut::<ButtonInput<GamepadButton>>();
+        buttons.release(button);
+        buttons.clear();
+    }
+
+    fn focused(app: &App) -> Option<Entity> {
+        app.world.resource::<Focus>().entity
+    }
+
+    fn activated(app: &mut App) -> Vec<Entity> {
+        app.world
+            .resource_mut::<Events<ButtonActivated>>()
+            .drain()
+            .map(|activated| activated.0)
+            .collect()
+    }
+
+    #[test]
+    fn focus_starts_on_the_first_widget_and_wraps_around() {
+        let (mut app, [first, second, back]) = app();
+        assert_eq!(focused(&app), Some(first));
+
+        press_key(&mut app, KeyCode::ArrowUp);
+        assert_eq!(focused(&app), Some(back));
+        press_key(&mut app, KeyCode::ArrowDown);
+        assert_eq!(focused(&app), Some(first));
+        press_key(&mut app, KeyCode::ArrowDown);
+        assert_eq!(focused(&app), Some(second));
+        press_pad(&mut app, GamepadButtonType::DPadDown);
+        assert_eq!(focused(&app), Some(back));
+        press_pad(&mut app, GamepadButtonType::DPadDown);
+        assert_eq!(focused(&app), Some(first));
+        assert_eq!(activated(&mut app), vec![]);
+    }
+
+    #[test]
+    fn escape_and_the_east_button_go_back() {
+        let (mut app, [first, _, back]) = app();
+
+        press_key(&mut app, KeyCode::Escape);
+        assert_eq!(activated(&mut app), vec![back]);
+        press_pad(&mut app, GamepadButtonType::East);
+        assert_eq!(activated(&mut app), vec![back]);
+        // Going back leaves the focus where it was
+        assert_eq!(focused(&app), Some(first));
+
+        press_key(&mut app, KeyCode::Enter);
+        assert_eq!(activated(&mut app), vec![first]);
+    }
+}
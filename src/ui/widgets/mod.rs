@@ -0,0 +1,376 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::assetloader::AssetRegistry;
+use crate::fonts::{FontStack, FontStackSystems, FontStackText};
+use crate::locale::UiText;
+
+pub mod button;
+pub mod dropdown;
+pub mod focus;
+pub mod scroll;
+pub mod slider;
+pub mod theme;
+pub mod toggle;
+
+pub use button::{ButtonActivated, WidgetButton};
+pub use dropdown::DropdownChanged;
+pub use focus::{BackButton, Focus, FocusScope, Focusable};
+pub use slider::{Slider, SliderChanged};
+pub use theme::UiTheme;
+pub use toggle::ToggleChanged;
+
+/// Behaviour of the widgets, menus only spawn them with `Widgets` and read
+/// their events
+pub struct WidgetsPlugin;
+
+impl Plugin for WidgetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiTheme>()
+            .init_resource::<Focus>()
+            .add_event::<ButtonActivated>()
+            .add_event::<SliderChanged>()
+            .add_event::<ToggleChanged>()
+            .add_event::<DropdownChanged>()
+            .add_systems(
+                Update,
+                (
+                    focus::navigate_focus,
+                    button::update_buttons,
+                    (toggle::flip_toggles, dropdown::pick_dropdowns),
+                )
+                    .chain()
+                    .in_set(WidgetSystems),
+            )
+            .add_systems(
+                Update,
+                (
+                    slider::drag_sliders,
+                    scroll::scroll_views,
+                    scroll::scroll_to_focus.after(focus::navigate_focus),
+                )
+                    .in_set(WidgetSystems),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    slider::draw_sliders,
+                    toggle::draw_toggles,
+                    dropdown::draw_dropdowns,
+                    focus::draw_focus,
+                )
+                    .before(FontStackSystems),
+            );
+    }
+}
+
+/// Systems turning input into widget events, menus reading the events run
+/// after them to react in the same frame
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct WidgetSystems;
+
+/// Spawns widgets in the style of a `UiTheme`
+#[derive(Clone, Copy)]
+pub struct Widgets<'a> {
+    theme: &'a UiTheme,
+    // Without a registry the text uses Bevy's built-in font
+    registry: Option<&'a AssetRegistry>,
+}
+
+impl<'a> Widgets<'a> {
+    pub fn new(theme: &'a UiTheme, registry: &'a AssetRegistry) -> Self {
+        Self {
+            theme,
+            registry: Some(registry),
+        }
+    }
+
+    /// Widgets for screens shown when the fonts may have failed to load
+    pub fn builtin_font(theme: &'a UiTheme) -> Self {
+        Self {
+            theme,
+            registry: None,
+        }
+    }
+
+    /// Spawn a full screen menu on the theme background, with its `bundle`
+    /// on the root, e.g. `DespawnOnExit(state)`
+    pub fn screen(
+        &self,
+        commands: &mut Commands,
+        bundle: impl Bundle,
+        content: impl FnOnce(&mut ChildBuilder),
+    ) -> Entity {
+        self.spawn_root(commands, bundle, self.theme.background, content)
+    }
+
+    /// Spawn a full screen menu letting the game show through
+    pub fn overlay(
+        &self,
+        commands: &mut Commands,
+        bundle: impl Bundle,
+        content: impl FnOnce(&mut ChildBuilder),
+    ) -> Entity {
+        self.spawn_root(commands, bundle, self.theme.overlay, content)
+    }
+
+    fn spawn_root(
+        &self,
+        commands: &mut Commands,
+        bundle: impl Bundle,
+        background: Color,
+        content: impl FnOnce(&mut ChildBuilder),
+    ) -> Entity {
+        commands
+            .spawn((
+                bundle,
+                FocusScope,
+                // Main node
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(self.theme.spacing),
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    visibility: Visibility::Visible,
+                    background_color: background.into(),
+                    ..default()
+                },
+            ))
+            .with_children(content)
+            .id()
+    }
+
+    /// Spawn a padded panel laying its content out in a column
+    pub fn panel<'b>(
+        &self,
+        parent: &'b mut ChildBuilder,
+        width: Val,
+        content: impl FnOnce(&mut ChildBuilder),
+    ) -> EntityCommands<'b> {
+        let mut panel = parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(self.theme.spacing),
+                padding: UiRect::all(Val::Px(2.0 * self.theme.spacing)),
+                width,
+                ..default()
+            },
+            visibility: Visibility::Visible,
+            background_color: self.theme.panel.into(),
+            ..default()
+        });
+        panel.with_children(content);
+        panel
+    }
+
+    /// Spawn a panel with a title bar
+    pub fn window<'b>(
+        &self,
+        parent: &'b mut ChildBuilder,
+        title: impl Into<UiText>,
+        width: Val,
+        content: impl FnOnce(&mut ChildBuilder),
+    ) -> EntityCommands<'b> {
+        let mut window = parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Stretch,
+                width,
+                ..default()
+            },
+            visibility: Visibility::Visible,
+            background_color: self.theme.panel.into(),
+            ..default()
+        });
+        window.with_children(|parent| {
+            // Title bar
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::Center,
+                        padding: UiRect::all(Val::Px(self.theme.spacing)),
+                        ..default()
+                    },
+                    background_color: self.theme.window_title_bar.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    self.spawn_ui_text(
+                        parent,
+                        title.into(),
+                        self.theme.heading_size,
+                        self.theme.window_title,
+                        self.theme.title_fonts,
+                    );
+                });
+            // Body
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(self.theme.spacing),
+                        padding: UiRect::all(Val::Px(2.0 * self.theme.spacing)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(content);
+        });
+        window
+    }
+
+    /// Spawn an unstyled column, its widgets stretch to its width
+    pub fn column<'b>(
+        &self,
+        parent: &'b mut ChildBuilder,
+        width: Val,
+        content: impl FnOnce(&mut ChildBuilder),
+    ) -> EntityCommands<'b> {
+        let mut column = parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Stretch,
+                row_gap: Val::Px(self.theme.spacing),
+                width,
+                ..default()
+            },
+            ..default()
+        });
+        column.with_children(content);
+        column
+    }
+
+    /// Spawn an unstyled row, its widgets share its width equally
+    pub fn row<'b>(
+        &self,
+        parent: &'b mut ChildBuilder,
+        width: Val,
+        content: impl FnOnce(&mut ChildBuilder),
+    ) -> EntityCommands<'b> {
+        let mut row = parent.spawn(NodeBundle {
+            style: Style {
+                display: Display::Grid,
+                grid_auto_flow: GridAutoFlow::Column,
+                grid_auto_columns: vec![GridTrack::flex(1.0)],
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(2.0 * self.theme.spacing),
+                width,
+                ..default()
+            },
+            ..default()
+        });
+        row.with_children(content);
+        row
+    }
+
+    pub fn title<'b>(
+        &self,
+        parent: &'b mut ChildBuilder,
+        value: impl Into<UiText>,
+    ) -> EntityCommands<'b> {
+        let (size, color, font) = (
+            self.theme.title_size,
+            self.theme.text,
+            self.theme.title_fonts,
+        );
+        self.spawn_ui_text(parent, value.into(), size, color, font)
+    }
+
+    pub fn heading<'b>(
+        &self,
+        parent: &'b mut ChildBuilder,
+        value: impl Into<UiText>,
+    ) -> EntityCommands<'b> {
+        let (size, color, font) = (
+            self.theme.heading_size,
+            self.theme.text,
+            self.theme.title_fonts,
+        );
+        self.spawn_ui_text(parent, value.into(), size, color, font)
+    }
+
+    pub fn text<'b>(
+        &self,
+        parent: &'b mut ChildBuilder,
+        value: impl Into<UiText>,
+    ) -> EntityCommands<'b> {
+        let (size, color, font) = (self.theme.text_size, self.theme.text, self.theme.text_fonts);
+        self.spawn_ui_text(parent, value.into(), size, color, font)
+    }
+
+    /// Spawn one line of small text made of differently coloured parts
+    pub fn rich_text<'b>(
+        &self,
+        parent: &'b mut ChildBuilder,
+        parts: &[(&str, Color)],
+    ) -> EntityCommands<'b> {
+        let sections = parts
+            .iter()
+            .map(|(value, color)| self.section(value, self.theme.small_text_size, *color))
+            .collect();
+        self.spawn_text(parent, sections, self.theme.text_fonts)
+    }
+
+    fn section(&self, value: &str, font_size: f32, color: Color) -> TextSection {
+        TextSection {
+            value: String::from(value),
+            style: TextStyle {
+                font_size,
+                color,
+                ..default()
+            },
+        }
+    }
+
+    // Spawn one section of text, localized text is filled in once the
+    // strings of the current language are looked up
+    fn spawn_ui_text<'b>(
+        &self,
+        parent: &'b mut ChildBuilder,
+        text: UiText,
+        font_size: f32,
+        color: Color,
+        fonts: FontStack,
+    ) -> EntityCommands<'b> {
+        let value = match &text {
+            UiText::Raw(value) => value.as_str(),
+            UiText::Localized(_) => "",
+        };
+        let section = self.section(value, font_size, color);
+        let mut entity = self.spawn_text(parent, vec![section], fonts);
+        if let UiText::Localized(localized) = text {
+            entity.insert(localized);
+        }
+        entity
+    }
+
+    // Spawn text drawn with the registry fonts of the stack, followed by
+    // the built-in font
+    fn spawn_text<'b>(
+        &self,
+        parent: &'b mut ChildBuilder,
+        sections: Vec<TextSection>,
+        fonts: FontStack,
+    ) -> EntityCommands<'b> {
+        let stack = if self.registry.is_some() { fonts } else { &[] };
+        parent.spawn((
+            FontStackText { stack, sections },
+            TextBundle {
+                text: Text {
+                    justify: JustifyText::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+    }
+}
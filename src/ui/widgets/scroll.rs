@@ -0,0 +1,161 @@
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+
+use super::{Focus, Widgets};
+use crate::locale::UiText;
+
+// Pixels scrolled per line of a mouse wheel
+const LINE_HEIGHT: f32 = 30.0;
+
+/// A box of fixed height whose content scrolls with the mouse wheel
+#[derive(Component, Default)]
+pub struct ScrollView {
+    // Pixels scrolled down from the top of the content
+    pub offset: f32,
+}
+
+#[derive(Component)]
+pub(super) struct ScrollContent;
+
+impl Widgets<'_> {
+    /// Spawn a scroll view with its content laid out in a column
+    pub fn scroll_view(
+        &self,
+        parent: &mut ChildBuilder,
+        height: Val,
+        content: impl FnOnce(&mut ChildBuilder),
+    ) -> Entity {
+        parent
+            .spawn((
+                ScrollView::default(),
+                // Hovered to scroll, without taking clicks from its content
+                Interaction::default(),
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        width: Val::Percent(100.0),
+                        height,
+                        overflow: Overflow::clip_y(),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent
+                    .spawn((
+                        ScrollContent,
+                        NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::FlexStart,
+                                row_gap: Val::Px(self.theme.spacing),
+                                width: Val::Percent(100.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ))
+                    .with_children(content);
+            })
+            .id()
+    }
+
+    /// Spawn a scroll view of a long text
+    pub fn scroll_text(
+        &self,
+        parent: &mut ChildBuilder,
+        value: impl Into<UiText>,
+        height: Val,
+    ) -> Entity {
+        self.scroll_view(parent, height, |parent| {
+            self.text(parent, value).insert(Style {
+                width: Val::Percent(100.0),
+                ..default()
+            });
+        })
+    }
+}
+
+/// Scroll the hovered scroll views with the mouse wheel
+pub(super) fn scroll_views(
+    mut wheel_events: EventReader<MouseWheel>,
+    mut view_query: Query<(&Interaction, &mut ScrollView, &Node, &Children)>,
+    mut content_query: Query<(&mut Style, &Node), With<ScrollContent>>,
+) {
+    let scrolled: f32 = wheel_events
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum();
+    if scrolled == 0.0 {
+        return;
+    }
+
+    for (interact, mut view, view_node, children) in &mut view_query {
+        if *interact == Interaction::None {
+            continue;
+        }
+        let offset = view.offset - scrolled;
+        scroll_to(&mut view, view_node, children, &mut content_query, offset);
+    }
+}
+
+/// Scroll the focused widget into its scroll view
+pub(super) fn scroll_to_focus(
+    focus: Res<Focus>,
+    parent_query: Query<&Parent>,
+    node_query: Query<(&Node, &GlobalTransform)>,
+    mut view_query: Query<(&mut ScrollView, &Node, &GlobalTransform, &Children)>,
+    mut content_query: Query<(&mut Style, &Node), With<ScrollContent>>,
+) {
+    if !focus.is_changed() {
+        return;
+    }
+    let Some(focused) = focus.entity else {
+        return;
+    };
+    let Some(view_entity) = parent_query
+        .iter_ancestors(focused)
+        .find(|ancestor| view_query.contains(*ancestor))
+    else {
+        return;
+    };
+    let (Ok((node, transform)), Ok((mut view, view_node, view_transform, children))) =
+        (node_query.get(focused), view_query.get_mut(view_entity))
+    else {
+        return;
+    };
+
+    // Node transforms are at their centre, with y growing downwards
+    let top = transform.translation().y - node.size().y / 2.0;
+    let bottom = transform.translation().y + node.size().y / 2.0;
+    let view_top = view_transform.translation().y - view_node.size().y / 2.0;
+    let view_bottom = view_transform.translation().y + view_node.size().y / 2.0;
+    let offset = if top < view_top {
+        view.offset - (view_top - top)
+    } else if bottom > view_bottom {
+        view.offset + (bottom - view_bottom)
+    } else {
+        return;
+    };
+    scroll_to(&mut view, view_node, children, &mut content_query, offset);
+}
+
+fn scroll_to(
+    view: &mut ScrollView,
+    view_node: &Node,
+    children: &Children,
+    content_query: &mut Query<(&mut Style, &Node), With<ScrollContent>>,
+    offset: f32,
+) {
+    for &child in children.iter() {
+        if let Ok((mut style, content_node)) = content_query.get_mut(child) {
+            let max_offset = (content_node.size().y - view_node.size().y).max(0.0);
+            view.offset = offset.clamp(0.0, max_offset);
+            style.top = Val::Px(-view.offset);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
+
+use super::{Focusable, Widgets};
+use crate::fonts::FontStackText;
+use crate::locale::UiText;
+
+/// A value picked between `min` and `max` in steps of `step` by dragging
+#[derive(Component, Clone, Debug)]
+pub struct Slider {
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+impl Slider {
+    pub fn new(value: f32, min: f32, max: f32, step: f32) -> Self {
+        let mut slider = Self {
+            value,
+            min,
+            max,
+            step,
+        };
+        slider.value = slider.snap(value);
+        slider
+    }
+
+    /// Round `value` to a step of the slider, within its range
+    pub fn snap(&self, value: f32) -> f32 {
+        let steps = ((value - self.min) / self.step).round();
+        (self.min + steps * self.step).clamp(self.min, self.max)
+    }
+
+    // Position of the value along the slider, from 0 to 1
+    fn fraction(&self) -> f32 {
+        if self.max > self.min {
+            (self.value - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        }
+    }
+
+    fn label(&self) -> String {
+        // As many decimals as the step has
+        let decimals = (-self.step.log10().floor()).max(0.0) as usize;
+        format!("{:.*}", decimals, self.value)
+    }
+}
+
+/// Sent when the value of a `Slider` is changed by the player
+#[derive(Event)]
+pub struct SliderChanged {
+    pub entity: Entity,
+    pub value: f32,
+}
+
+// Filled part of a slider track
+#[derive(Component)]
+pub(super) struct SliderFill;
+
+#[derive(Component)]
+pub(super) struct SliderValue;
+
+impl Widgets<'_> {
+    /// Spawn a labelled slider, with `bundle` put next to the `Slider` and
+    /// the events sent for its entity
+    pub fn slider(
+        &self,
+        parent: &mut ChildBuilder,
+        label: impl Into<UiText>,
+        slider: Slider,
+        bundle: impl Bundle,
+    ) -> Entity {
+        let fraction = slider.fraction();
+        let value = slider.label();
+        let mut track = Entity::PLACEHOLDER;
+        self.row(parent, Val::Percent(100.0), |parent| {
+            self.text(parent, label);
+            track = parent
+                .spawn((
+                    bundle,
+                    slider,
+                    Focusable,
+                    Interaction::default(),
+                    RelativeCursorPosition::default(),
+                    NodeBundle {
+                        style: Style {
+                            height: Val::Px(0.6 * self.theme.button_height),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: self.theme.track.into(),
+                        focus_policy: FocusPolicy::Block,
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        SliderFill,
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(0.0),
+                                width: Val::Percent(100.0 * fraction),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: self.theme.accent.into(),
+                            ..default()
+                        },
+                    ));
+                    self.text(parent, value).insert(SliderValue);
+                })
+                .id();
+        });
+        track
+    }
+}
+
+/// Move the pressed sliders to the cursor
+pub(super) fn drag_sliders(
+    mut slider_query: Query<(Entity, &Interaction, &RelativeCursorPosition, &mut Slider)>,
+    mut changed_events: EventWriter<SliderChanged>,
+) {
+    for (entity, interact, cursor, mut slider) in &mut slider_query {
+        if *interact != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+        let fraction = position.x.clamp(0.0, 1.0);
+        let value = slider.snap(slider.min + fraction * (slider.max - slider.min));
+        if value != slider.value {
+            slider.value = value;
+            changed_events.send(SliderChanged { entity, value });
+        }
+    }
+}
+
+pub(super) fn draw_sliders(
+    slider_query: Query<(&Slider, &Children), Changed<Slider>>,
+    mut fill_query: Query<&mut Style, With<SliderFill>>,
+    mut value_query: Query<&mut FontStackText, With<SliderValue>>,
+) {
+    for (slider, children) in slider_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut style) = fill_query.get_mut(child) {
+                style.width = Val::Percent(100.0 * slider.fraction());
+            }
+            if let Ok(mut text) = value_query.get_mut(child) {
+                text.sections[0].value = slider.label();
+            }
+        }
+    }
+}
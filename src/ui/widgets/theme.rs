@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+
+use crate::fonts::FontStack;
+
+/// Fonts, sizes and colours shared by every widget
+#[derive(Resource, Clone, Debug)]
+pub struct UiTheme {
+    // Keys of the fonts in the asset registry, the first one having a glyph
+    // draws it
+    pub title_fonts: FontStack,
+    pub text_fonts: FontStack,
+
+    pub title_size: f32,
+    pub heading_size: f32,
+    pub text_size: f32,
+    pub small_text_size: f32,
+
+    // Gap between widgets, panels are padded with twice as much
+    pub spacing: f32,
+    pub button_height: f32,
+
+    pub background: Color,
+    // Background of menus shown over the running game
+    pub overlay: Color,
+    pub panel: Color,
+    pub window_title_bar: Color,
+    pub window_title: Color,
+    pub text: Color,
+    pub error_text: Color,
+
+    pub button: Color,
+    pub button_hovered: Color,
+    pub button_pressed: Color,
+    pub button_disabled: Color,
+    pub button_text: Color,
+    pub button_text_disabled: Color,
+
+    // Filled part of sliders and toggles that are on
+    pub accent: Color,
+    // Empty part of sliders and toggles that are off
+    pub track: Color,
+    // Outline of the widget focused with the keyboard or a gamepad
+    pub focus_outline: Color,
+}
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        Self {
+            title_fonts: &["ui_bold", "ui_normal"],
+            text_fonts: &["ui_normal"],
+
+            title_size: 100.0,
+            heading_size: 50.0,
+            text_size: 30.0,
+            small_text_size: 20.0,
+
+            spacing: 10.0,
+            button_height: 50.0,
+
+            background: Color::ANTIQUE_WHITE,
+            overlay: Color::rgba(0.0, 0.0, 0.0, 0.6),
+            panel: Color::ANTIQUE_WHITE,
+            window_title_bar: Color::DARK_GRAY,
+            window_title: Color::ANTIQUE_WHITE,
+            text: Color::GRAY,
+            error_text: Color::MAROON,
+
+            button: Color::YELLOW_GREEN,
+            button_hovered: Color::rgb(0.7, 0.9, 0.3),
+            button_pressed: Color::ALICE_BLUE,
+            button_disabled: Color::SILVER,
+            button_text: Color::BLUE,
+            button_text_disabled: Color::GRAY,
+
+            accent: Color::YELLOW_GREEN,
+            track: Color::SILVER,
+            focus_outline: Color::ORANGE_RED,
+        }
+    }
+}
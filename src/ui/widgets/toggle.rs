@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+
+use super::{ButtonActivated, Focusable, UiTheme, WidgetButton, Widgets};
+use crate::locale::UiText;
+
+/// A labelled switch, flipped when activated like a button
+#[derive(Component, Clone, Debug)]
+pub struct Toggle {
+    pub on: bool,
+}
+
+/// Sent when a `Toggle` is flipped by the player
+#[derive(Event)]
+pub struct ToggleChanged {
+    pub entity: Entity,
+    pub on: bool,
+}
+
+// Box showing whether a toggle is on
+#[derive(Component)]
+pub(super) struct ToggleBox;
+
+impl Widgets<'_> {
+    /// Spawn a toggle, with `bundle` put next to the `Toggle` and the events
+    /// sent for its entity
+    pub fn toggle(
+        &self,
+        parent: &mut ChildBuilder,
+        label: impl Into<UiText>,
+        on: bool,
+        bundle: impl Bundle,
+    ) -> Entity {
+        let size = Val::Px(0.5 * self.theme.button_height);
+        parent
+            .spawn((
+                bundle,
+                Toggle { on },
+                Focusable,
+                WidgetButton::default(),
+                self.button_bundle(),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    ToggleBox,
+                    NodeBundle {
+                        style: Style {
+                            width: size,
+                            height: size,
+                            ..default()
+                        },
+                        background_color: toggle_color(self.theme, on).into(),
+                        ..default()
+                    },
+                ));
+                self.button_label(parent, label.into());
+            })
+            .id()
+    }
+}
+
+fn toggle_color(theme: &UiTheme, on: bool) -> Color {
+    if on {
+        theme.accent
+    } else {
+        theme.track
+    }
+}
+
+pub(super) fn flip_toggles(
+    mut activated_events: EventReader<ButtonActivated>,
+    mut toggle_query: Query<&mut Toggle>,
+    mut changed_events: EventWriter<ToggleChanged>,
+) {
+    for ButtonActivated(entity) in activated_events.read() {
+        if let Ok(mut toggle) = toggle_query.get_mut(*entity) {
+            toggle.on = !toggle.on;
+            changed_events.send(ToggleChanged {
+                entity: *entity,
+                on: toggle.on,
+            });
+        }
+    }
+}
+
+pub(super) fn draw_toggles(
+    theme: Res<UiTheme>,
+    toggle_query: Query<(&Toggle, &Children), Changed<Toggle>>,
+    mut box_query: Query<&mut BackgroundColor, With<ToggleBox>>,
+) {
+    for (toggle, children) in toggle_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut backgroundcolor) = box_query.get_mut(child) {
+                *backgroundcolor = toggle_color(&theme, toggle.on).into();
+            }
+        }
+    }
+}
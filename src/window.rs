@@ -1,26 +1,112 @@
+use bevy::input::InputSystem;
 use bevy::prelude::*;
+use bevy::ui::UiSystem;
+use bevy::window::{PrimaryWindow, WindowMode, WindowPosition, WindowResized};
+use std::time::Duration;
 
-const BACKGROUND_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+use crate::settings::{DisplayMode, Settings};
+
+// Window height the menus are laid out for, they are scaled to the actual one
+const UI_REFERENCE_HEIGHT: f32 = 1080.0;
+
+// Time the window stays put before its placement is kept, so dragging or
+// resizing it does not save the settings every frame
+const SETTLE_TIME: Duration = Duration::from_millis(500);
 
 pub struct WindowPlugin;
 
 impl Plugin for WindowPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, window_setup);
+        app.add_systems(Startup, window_setup)
+            // Before the menus and the game see the Enter press
+            .add_systems(
+                PreUpdate,
+                toggle_fullscreen.after(InputSystem).before(UiSystem::Focus),
+            )
+            .add_systems(Update, (remember_window, scale_ui).chain());
     }
 }
 
-fn window_setup(mut commands: Commands, mut windows: Query<&mut Window>) {
-    // Set windows size
+fn window_setup(mut windows: Query<&mut Window>) {
+    // Set windows title
     let mut window = windows.single_mut();
     window.title = String::from("Roguelike demo");
+}
+
+/// Alt+Enter switches between windowed and borderless fullscreen
+fn toggle_fullscreen(mut keys: ResMut<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    if !alt || !keys.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    keys.clear_just_pressed(KeyCode::Enter);
+    settings.display.mode = match settings.display.mode {
+        DisplayMode::Windowed => DisplayMode::BorderlessFullscreen,
+        _ => DisplayMode::Windowed,
+    };
+}
+
+// Size and position of the window not kept yet, and when they changed
+struct PendingPlacement {
+    resolution: (u32, u32),
+    position: Option<(i32, i32)>,
+    changed_at: Duration,
+}
 
-    // Set camera
-    commands.spawn(Camera2dBundle {
-        camera: Camera {
-            clear_color: ClearColorConfig::Custom(BACKGROUND_COLOR),
-            ..default()
-        },
-        ..default()
-    });
+/// Keep the size and position the player gave the window, to open it there
+/// next time, once the window has settled
+fn remember_window(
+    window_query: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    time: Res<Time<Real>>,
+    mut pending: Local<Option<PendingPlacement>>,
+    mut settings: ResMut<Settings>,
+) {
+    let windowed = settings.display.mode == DisplayMode::Windowed;
+    if let Ok(window) = window_query.get_single() {
+        *pending = (windowed && window.mode == WindowMode::Windowed).then(|| PendingPlacement {
+            resolution: (
+                window.width().round() as u32,
+                window.height().round() as u32,
+            ),
+            position: match window.position {
+                WindowPosition::At(position) => Some((position.x, position.y)),
+                _ => settings.display.position,
+            },
+            changed_at: time.elapsed(),
+        });
+    }
+    let settled = pending
+        .as_ref()
+        .is_some_and(|pending| time.elapsed() - pending.changed_at >= SETTLE_TIME);
+    if !windowed || !settled {
+        return;
+    }
+    let Some(placement) = pending.take() else {
+        return;
+    };
+    let display = &settings.display;
+    if display.resolution != placement.resolution || display.position != placement.position {
+        settings.display.resolution = placement.resolution;
+        settings.display.position = placement.position;
+    }
+}
+
+/// Scale the menus to the window height
+fn scale_ui(
+    settings: Res<Settings>,
+    mut resized_events: EventReader<WindowResized>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let resized = resized_events.read().count() > 0;
+    if !resized && !settings.is_changed() {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let scale = settings.ui_scale * window.height() / UI_REFERENCE_HEIGHT;
+    if ui_scale.0 != scale {
+        ui_scale.0 = scale;
+    }
 }